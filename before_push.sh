@@ -1,4 +1,6 @@
 # Sanity checks before commit to repo. Mimic CI checks
 cargo test --all
+cargo test --all --all-features
 cargo fmt --all -- --check
-cargo clippy -- -D warnings
\ No newline at end of file
+cargo clippy -- -D warnings
+cargo clippy --all-features -- -D warnings
@@ -1,5 +1,7 @@
+use std::alloc::{GlobalAlloc, Layout, System};
 use std::iter::StepBy;
 use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use ego_tree::NodeMut as ENodeMut;
@@ -7,13 +9,44 @@ use ego_tree::Tree as ETree;
 
 use tree_flat::prelude::*;
 
+// Count the allocations, to report them in the benchmarks that avoid them
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+// The allocations (& reallocations) done by `f`
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
 const RUNS_SIMPLE: u64 = 100;
 const RUNS_HIERARCHY: u64 = 100;
 
 // Pick one of the child nodes at level 1 (first the 1st, then some around the middle)
 // based on RUNS_HIERARCHY / 4 values...
 fn _get_child_node(run: u64) -> u64 {
-    assert!(RUNS_HIERARCHY >= 100);
+    const _: () = assert!(RUNS_HIERARCHY >= 100);
     match run {
         0 => 1,
         25 => 3_628,
@@ -27,7 +60,7 @@ fn _get_child_node(run: u64) -> u64 {
 // Pick of the highest node at the end
 // based on RUNS_HIERARCHY / 4 values...
 fn _get_parent_node(run: u64) -> u64 {
-    assert!(RUNS_HIERARCHY >= 100);
+    const _: () = assert!(RUNS_HIERARCHY >= 100);
     match run {
         0 => 18,
         25 => 3_618,
@@ -115,7 +148,14 @@ mod ego {
         let idx = _get_parent_node(run);
         let node = t.nodes().into_iter().find(|x| *x.value() == idx).unwrap();
 
-        for _x in node.parent() {}
+        if let Some(_x) = node.parent() {}
+    }
+
+    pub(crate) fn try_for_each(_run: u64, t: ETree<u64>) {
+        let max = t.values().count() as u64;
+        let _ = t
+            .values()
+            .try_for_each(|x| if *x + 1 == max { Err(*x) } else { Ok(()) });
     }
 
     pub(crate) fn create(n: u64) {
@@ -155,6 +195,11 @@ mod flat {
         for _x in node.children() {}
     }
 
+    pub(crate) fn try_for_each(_run: u64, t: Tree<u64>) {
+        let max = t.len() as u64;
+        let _ = t.try_for_each_data(|x| if *x + 1 == max { Err(*x) } else { Ok(()) });
+    }
+
     pub(crate) fn create(n: u64) {
         let mut tree = Tree::with_capacity(0, n as usize);
 
@@ -174,13 +219,13 @@ pub fn make_benchmark<E, F>(
     f_ego: F,
     f_flat: E,
 ) where
-    F: Fn(u64) -> (),
-    E: Fn(u64) -> (),
+    F: Fn(u64),
+    E: Fn(u64),
 {
     let mut group = c.benchmark_group(name);
 
     for runs in range {
-        group.throughput(Throughput::Elements(runs as u64));
+        group.throughput(Throughput::Elements(runs));
 
         group.bench_with_input(BenchmarkId::new("Ego", id), &runs, |b, i| {
             b.iter(|| f_ego(*i))
@@ -193,6 +238,7 @@ pub fn make_benchmark<E, F>(
     group.finish();
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn make_benchmark_prep<E, F, TE, TF>(
     c: &mut Criterion,
     name: &str,
@@ -204,14 +250,14 @@ pub fn make_benchmark_prep<E, F, TE, TF>(
     f_flat: F,
 ) where
     TE: Fn(u64) -> ETree<u64>,
-    E: Fn(u64, ETree<u64>) -> (),
+    E: Fn(u64, ETree<u64>),
     TF: Fn(u64) -> Tree<u64>,
-    F: Fn(u64, Tree<u64>) -> (),
+    F: Fn(u64, Tree<u64>),
 {
     let mut group = c.benchmark_group(name);
 
     for runs in range {
-        group.throughput(Throughput::Elements(runs as u64));
+        group.throughput(Throughput::Elements(runs));
 
         let et = t_ego(runs);
         let ft = t_flat(runs);
@@ -296,12 +342,86 @@ pub fn iter_parents(c: &mut Criterion) {
     )
 }
 
+// Check scanning the data until the last one
+pub fn try_for_each(c: &mut Criterion) {
+    let range = (0..=RUNS_HIERARCHY).step_by((RUNS_HIERARCHY / 4) as usize);
+    make_benchmark_prep(
+        c,
+        "Try for each data",
+        6,
+        range,
+        ego::_create_hierarchy,
+        ego::try_for_each,
+        flat::_create_hierarchy,
+        flat::try_for_each,
+    )
+}
+
+// Check walking only the structure, with small, large & boxed large data
+pub fn iter_structure(c: &mut Criterion) {
+    fn walk<T: std::fmt::Debug>(t: &Tree<T>) -> usize {
+        t.nodes().map(|x| x.level() + x.parent()).sum()
+    }
+
+    let mut group = c.benchmark_group("Iter Tree structure");
+    for runs in (0..=RUNS_HIERARCHY).step_by((RUNS_HIERARCHY / 4) as usize) {
+        group.throughput(Throughput::Elements(runs));
+
+        let small = flat::_create_hierarchy(runs);
+        let large = small.clone().map_into(|x| [x; 128]);
+        let boxed = large.clone().boxed();
+
+        group.bench_with_input(BenchmarkId::new("Small", 7), &small, |b, t| {
+            b.iter(|| walk(t))
+        });
+        group.bench_with_input(BenchmarkId::new("Large", 7), &large, |b, t| {
+            b.iter(|| walk(t))
+        });
+        group.bench_with_input(BenchmarkId::new("Boxed", 7), &boxed, |b, t| {
+            b.iter(|| walk(t))
+        });
+    }
+
+    group.finish();
+}
+
+// Check pretty-printing, building the columns per tree or reusing an IndentCache,
+// and report the allocations of each
+pub fn print(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Print");
+    for runs in (0..=RUNS_HIERARCHY).step_by((RUNS_HIERARCHY / 4) as usize) {
+        group.throughput(Throughput::Elements(runs));
+
+        let tree = flat::_create_hierarchy(runs);
+        let cache = tree.indent_cache(16);
+
+        let plain = allocations(|| tree.write_tree(&mut std::io::sink()).unwrap());
+        let cached = allocations(|| tree.write_tree_with(&mut std::io::sink(), &cache).unwrap());
+        println!(
+            "Print/{}: {} allocations, {} with an IndentCache",
+            runs, plain, cached
+        );
+
+        group.bench_with_input(BenchmarkId::new("Build", 8), &tree, |b, t| {
+            b.iter(|| t.write_tree(&mut std::io::sink()))
+        });
+        group.bench_with_input(BenchmarkId::new("Cached", 8), &tree, |b, t| {
+            b.iter(|| t.write_tree_with(&mut std::io::sink(), &cache))
+        });
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     create,
     hierarchy,
     hierarchy_iter,
     iter_children,
-    iter_parents
+    iter_parents,
+    try_for_each,
+    iter_structure,
+    print
 );
 criterion_main!(benches);
@@ -48,7 +48,8 @@ fn walk_dir_manual(path: &str) -> io::Result<Tree<String>> {
 
 fn walk_dir(path: &str) -> io::Result<Tree<String>> {
     let mut tree = Tree::new(path.to_string());
-    let mut parent = tree.tree_root_mut().parent;
+    //The last directory at each depth, so we know the parent of the next entry
+    let mut parents = vec![tree.tree_root_mut().id];
     for entry in WalkDir::new(path)
         .into_iter()
         .filter_entry(|f| !ignore(f.file_name()))
@@ -58,14 +59,16 @@ fn walk_dir(path: &str) -> io::Result<Tree<String>> {
         if entry.depth() == 0 {
             continue;
         }
-        let node_id = tree.push_with_level(
-            path_to_str(entry.path().to_path_buf()),
-            entry.depth(),
-            parent,
-        );
+        parents.truncate(entry.depth());
+        let node_id = tree
+            .push_child_at(
+                parents[entry.depth() - 1],
+                path_to_str(entry.path().to_path_buf()),
+            )
+            .expect("walkdir traverse in pre-order");
 
         if entry.path().is_dir() {
-            parent = node_id;
+            parents.push(node_id);
         }
     }
     println!("{}", &tree);
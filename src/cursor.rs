@@ -0,0 +1,117 @@
+use std::fmt::Debug;
+
+use crate::prelude::*;
+
+/// A builder over a [Tree] that tracks the current insertion point, like a
+/// recursive-descent parser does.
+///
+/// The nodes are always pushed at the end, so the tree stays in pre-order.
+#[derive(Debug)]
+pub struct Cursor<'a, T: 'a> {
+    pub(crate) current: NodeId,
+    pub(crate) tree: &'a mut Tree<T>,
+}
+
+impl<'a, T: Debug + 'a> Cursor<'a, T> {
+    /// The [NodeId] of the current insertion point.
+    pub fn current(&self) -> NodeId {
+        self.current
+    }
+
+    /// Push a child of the current node, and descend into it.
+    pub fn down(&mut self, data: T) -> &mut Self {
+        let level = self.tree.get_level(self.current) + 1;
+        self.current = self.tree.push_with_level(data, level, self.current);
+        self
+    }
+
+    /// Push a sibling of the current node, and move into it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current node is the root, that can't have siblings.
+    pub fn sibling(&mut self, data: T) -> &mut Self {
+        let idx = self.current.to_index();
+        assert!(idx > 0, "the root can't have siblings");
+        let level = self.tree.level[idx];
+        let parent = self.tree.parent[idx].into();
+        self.current = self.tree.push_with_level(data, level, parent);
+        self
+    }
+
+    /// Ascend to the parent of the current node.
+    ///
+    /// Does nothing if the current node is the root.
+    pub fn up(&mut self) -> &mut Self {
+        self.current = self.tree.parent[self.current.to_index()].into();
+        self
+    }
+}
+
+impl<T: Debug> Tree<T> {
+    /// Get a [Cursor] at the root, to build the tree moving up & down
+    pub fn cursor(&mut self) -> Cursor<'_, T> {
+        Cursor {
+            current: NodeId::ROOT,
+            tree: self,
+        }
+    }
+}
+
+/// A depth-first cursor over a [Tree], with an explicit stack of frames, to
+/// implement custom traversals (like with accumulators per depth).
+#[derive(Debug)]
+pub struct DfsCursor<'a, T: 'a> {
+    // Each frame is the node & the next child to visit
+    pub(crate) stack: Vec<(usize, usize)>,
+    pub(crate) tree: &'a Tree<T>,
+}
+
+impl<'a, T: Debug + 'a> DfsCursor<'a, T> {
+    /// The current [Node].
+    pub fn current(&self) -> Node<'a, T> {
+        let (idx, _) = self.stack[self.stack.len() - 1];
+        self.tree._make_node(idx.into())
+    }
+
+    /// How many frames are below the current one.
+    pub fn depth(&self) -> usize {
+        self.stack.len() - 1
+    }
+
+    /// Push a frame for the next not visited child of the current node, and
+    /// return it. Returns [None] if all the children were visited.
+    pub fn descend(&mut self) -> Option<Node<'a, T>> {
+        let last = self.stack.len() - 1;
+        let (idx, next) = self.stack[last];
+        if next < self.tree._subtree_end(idx) {
+            self.stack[last].1 = self.tree._subtree_end(next);
+            self.stack.push((next, next + 1));
+            Some(self.tree._make_node(next.into()))
+        } else {
+            None
+        }
+    }
+
+    /// Pop the frame of the current node, returning to its parent.
+    ///
+    /// Returns `false` if the current node is the starting one.
+    pub fn ascend(&mut self) -> bool {
+        if self.stack.len() > 1 {
+            self.stack.pop();
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl<T: Debug> Tree<T> {
+    /// Get a [DfsCursor] at the root
+    pub fn dfs_cursor(&self) -> DfsCursor<'_, T> {
+        DfsCursor {
+            stack: vec![(0, 1)],
+            tree: self,
+        }
+    }
+}
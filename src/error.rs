@@ -0,0 +1,148 @@
+use std::fmt::{Display, Formatter};
+
+use crate::node::NodeId;
+
+/// The errors when building or editing a [crate::tree::Tree].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TreeError {
+    /// The root of the tree was not found.
+    MissingRoot,
+    /// A node points to a parent that was not found.
+    MissingParent,
+    /// Some nodes are not reachable from the root, because they are in a cycle.
+    Cycle,
+    /// The tree has no nodes.
+    Empty,
+    /// The internal vectors don't have the same length.
+    LengthMismatch,
+    /// The node has a parent that don't match the pre-order.
+    InvalidParent(NodeId),
+    /// The node has a level that don't match the pre-order.
+    InvalidLevel(NodeId),
+    /// The subtree of the node is not at the end of the tree, so a new child will
+    /// break the pre-order.
+    NotAtTail(NodeId),
+    /// The trees don't have the same levels & parents.
+    ShapeMismatch,
+    /// The event at the position closes a node that was not opened, opens a second
+    /// root, or (at the end) some nodes were not closed.
+    UnbalancedEvent(usize),
+    /// The subtrees of both nodes overlap, because one contains the other.
+    Overlapping(NodeId, NodeId),
+}
+
+impl Display for TreeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TreeError::MissingRoot => write!(f, "The root was not found"),
+            TreeError::MissingParent => write!(f, "The parent of a node was not found"),
+            TreeError::Cycle => write!(f, "The nodes are in a cycle"),
+            TreeError::Empty => write!(f, "The tree is empty"),
+            TreeError::LengthMismatch => write!(f, "The vectors have different lengths"),
+            TreeError::InvalidParent(id) => write!(f, "The parent of {} is invalid", id),
+            TreeError::InvalidLevel(id) => write!(f, "The level of {} is invalid", id),
+            TreeError::NotAtTail(id) => write!(f, "The subtree of {} is not at the end", id),
+            TreeError::ShapeMismatch => write!(f, "The trees have different shapes"),
+            TreeError::UnbalancedEvent(pos) => write!(f, "The event at {} is unbalanced", pos),
+            TreeError::Overlapping(a, b) => write!(f, "The subtrees of {} and {} overlap", a, b),
+        }
+    }
+}
+
+impl std::error::Error for TreeError {}
+
+/// A structural problem found by [crate::tree::Tree::validate], with the index of
+/// the node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValidationError {
+    /// The tree has no nodes.
+    Empty,
+    /// The internal vectors don't have the same length.
+    LengthMismatch {
+        data: usize,
+        level: usize,
+        parent: usize,
+    },
+    /// The root is not at level 0 or is not his own parent.
+    InvalidRoot { level: usize, parent: usize },
+    /// A node besides the root is at level 0.
+    ExtraRoot { node: usize },
+    /// The node is deeper than the child of the previous node allows.
+    LevelJump {
+        node: usize,
+        expected: usize,
+        got: usize,
+    },
+    /// The parent is not before the node, so it breaks the pre-order.
+    ParentNotBefore { node: usize, parent: usize },
+    /// The parent is before the node, but is not the last node of the level above.
+    ParentMismatch {
+        node: usize,
+        expected: usize,
+        got: usize,
+    },
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::Empty => write!(f, "The tree is empty"),
+            ValidationError::LengthMismatch {
+                data,
+                level,
+                parent,
+            } => write!(
+                f,
+                "The vectors have different lengths: data {}, level {}, parent {}",
+                data, level, parent
+            ),
+            ValidationError::InvalidRoot { level, parent } => write!(
+                f,
+                "The root has level {} and parent {}, expected 0",
+                level, parent
+            ),
+            ValidationError::ExtraRoot { node } => write!(f, "The node {} is at level 0", node),
+            ValidationError::LevelJump {
+                node,
+                expected,
+                got,
+            } => write!(
+                f,
+                "The node {} is at level {}, expected at most {}",
+                node, got, expected
+            ),
+            ValidationError::ParentNotBefore { node, parent } => {
+                write!(f, "The parent {} is not before the node {}", parent, node)
+            }
+            ValidationError::ParentMismatch {
+                node,
+                expected,
+                got,
+            } => write!(
+                f,
+                "The node {} has parent {}, expected {}",
+                node, got, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl From<ValidationError> for TreeError {
+    fn from(x: ValidationError) -> Self {
+        match x {
+            ValidationError::Empty => TreeError::Empty,
+            ValidationError::LengthMismatch { .. } => TreeError::LengthMismatch,
+            ValidationError::InvalidRoot { level, .. } if level != 0 => {
+                TreeError::InvalidLevel(0.into())
+            }
+            ValidationError::InvalidRoot { .. } => TreeError::InvalidParent(0.into()),
+            ValidationError::ExtraRoot { node } | ValidationError::LevelJump { node, .. } => {
+                TreeError::InvalidLevel(node.into())
+            }
+            ValidationError::ParentNotBefore { node, .. }
+            | ValidationError::ParentMismatch { node, .. } => TreeError::InvalidParent(node.into()),
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use std::fmt::Debug;
+use std::iter::Map;
 
 use crate::prelude::*;
 
@@ -7,6 +8,18 @@ pub struct TreeIter<'a, T> {
     pub(crate) tree: &'a Tree<T>,
 }
 
+impl<T: Debug> TreeIter<'_, T> {
+    /// Skip the subtree of the last yielded node, so the iteration continues
+    /// with its next sibling (or the next node after the subtree).
+    ///
+    /// Does nothing if no node was yielded yet.
+    pub fn skip_subtree(&mut self) {
+        if self.pos > 0 && self.pos <= self.tree.len() {
+            self.pos = self.tree._subtree_end(self.pos - 1);
+        }
+    }
+}
+
 impl<'a, T: Debug> Iterator for TreeIter<'a, T> {
     type Item = Node<'a, T>;
 
@@ -21,15 +34,19 @@ impl<'a, T: Debug> Iterator for TreeIter<'a, T> {
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        (0, Some(self.tree.len()))
+        let len = self.tree.len().saturating_sub(self.pos);
+        (len, Some(len))
     }
 }
 
-pub struct IntoIter<'a, T> {
+impl<T: Debug> ExactSizeIterator for TreeIter<'_, T> {}
+
+/// A borrowing [IntoIterator] of the nodes of a [Tree], see [Tree::into_iter_ref].
+pub struct IntoIterRef<'a, T> {
     pub(crate) tree: &'a Tree<T>,
 }
 
-impl<'a, T: Debug> IntoIterator for IntoIter<'a, T> {
+impl<'a, T: Debug> IntoIterator for IntoIterRef<'a, T> {
     type Item = Node<'a, T>;
     type IntoIter = TreeIter<'a, T>;
 
@@ -50,10 +67,57 @@ impl<'a, T: Debug> IntoIterator for &'a Tree<T> {
     }
 }
 
+/// A consuming [Iterator] that moves out the data of a [Tree] in pre-order.
+#[derive(Debug)]
+pub struct IntoIter<T> {
+    pub(crate) data: std::vec::IntoIter<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.data.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.data.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.data.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for Tree<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            data: self.data.into_iter(),
+        }
+    }
+}
+
+/// An [Iterator] of the parents of a [Node], see [Node::parents].
+///
+/// It yields from the parent up to the root, so the levels are strictly
+/// decreasing. Reversed, it yields from the root down to the parent.
 #[derive(Debug)]
 pub struct ParentIter<'a, T> {
-    pub(crate) parent: usize,
+    /// The node with the parents still to yield from the front.
     pub(crate) node: NodeId,
+    /// The level of the next parent to yield from the back.
+    pub(crate) back: usize,
+    /// The parents still to yield from the back, the shallower at the top.
+    ///
+    /// Filled on the first [Self::next_back], so `.rev()` walks up only once.
+    pub(crate) stack: Vec<usize>,
     pub(crate) tree: &'a Tree<T>,
 }
 
@@ -61,17 +125,48 @@ impl<'a, T: Debug> Iterator for ParentIter<'a, T> {
     type Item = Node<'a, T>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        // dbg!(self.pos, self.parent, self.node.0);
-        if self.node.to_index() > 0 {
-            self.node = NodeId::from_index(self.parent);
-            self.parent = self.tree.parent[self.parent];
+        if self.back < self.tree.level[self.node.to_index()] {
+            self.node = self.tree.parent[self.node.to_index()].into();
             Some(self.tree._make_node(self.node))
         } else {
             None
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.tree.level[self.node.to_index()].saturating_sub(self.back);
+        (len, Some(len))
+    }
 }
 
+impl<T: Debug> DoubleEndedIterator for ParentIter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.back < self.tree.level[self.node.to_index()] {
+            if self.stack.is_empty() {
+                let mut idx = self.node.to_index();
+                while self.tree.level[idx] > self.back {
+                    idx = self.tree.parent[idx];
+                    self.stack.push(idx);
+                }
+            }
+            // The deeper ones at the bottom could be already yielded by `next`,
+            // but then they are never reached
+            let idx = self.stack.pop()?;
+            self.back += 1;
+            Some(self.tree._make_node(idx.into()))
+        } else {
+            None
+        }
+    }
+}
+
+impl<T: Debug> ExactSizeIterator for ParentIter<'_, T> {}
+
+/// The [ParentIter] paired with the level of each parent, see
+/// [Node::ancestors_with_level].
+pub type AncestorsWithLevel<'a, T> =
+    Map<ParentIter<'a, T>, fn(Node<'a, T>) -> (Node<'a, T>, usize)>;
+
 #[derive(Debug)]
 pub struct ChildrenIter<'a, T> {
     pub(crate) pos: usize,
@@ -155,3 +250,38 @@ impl<'a, T: Debug> Iterator for SiblingsIter<'a, T> {
         }
     }
 }
+
+/// A draining [Iterator] of the `(data, level, parent)` of a range of a [Tree],
+/// see [Tree::drain].
+#[derive(Debug)]
+pub struct Drain<'a, T> {
+    pub(crate) data: std::vec::Drain<'a, T>,
+    pub(crate) level: std::vec::Drain<'a, usize>,
+    pub(crate) parent: std::vec::Drain<'a, usize>,
+}
+
+impl<T> Iterator for Drain<'_, T> {
+    type Item = (T, usize, NodeId);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let data = self.data.next()?;
+        let level = self.level.next().unwrap();
+        let parent = self.parent.next().unwrap().into();
+        Some((data, level, parent))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.data.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Drain<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let data = self.data.next_back()?;
+        let level = self.level.next_back().unwrap();
+        let parent = self.parent.next_back().unwrap().into();
+        Some((data, level, parent))
+    }
+}
+
+impl<T> ExactSizeIterator for Drain<'_, T> {}
@@ -0,0 +1,142 @@
+use std::cmp::Ordering;
+use std::fmt::Debug;
+
+use crate::prelude::*;
+
+/// A rectangle, as returned by [Tree::treemap_layout].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Rect {
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn area(&self) -> f64 {
+        self.width * self.height
+    }
+}
+
+// The worst aspect ratio of the areas of a `row` placed along `side`
+fn worst(row: &[(usize, f64)], side: f64) -> f64 {
+    let sum: f64 = row.iter().map(|(_, area)| area).sum();
+    let (min, max) = row
+        .iter()
+        .fold((f64::MAX, 0.0f64), |(min, max), (_, area)| {
+            (min.min(*area), max.max(*area))
+        });
+    let side = side * side;
+    let sum = sum * sum;
+    (side * max / sum).max(sum / (side * min))
+}
+
+// Place the `row` along the short side of `rect`, and shrink it to the free space
+fn layout_row(row: &[(usize, f64)], rect: &mut Rect, out: &mut [Rect]) {
+    let sum: f64 = row.iter().map(|(_, area)| area).sum();
+    if rect.width >= rect.height {
+        let width = if rect.height > 0.0 {
+            sum / rect.height
+        } else {
+            0.0
+        };
+        let mut y = rect.y;
+        for (idx, area) in row {
+            let height = if width > 0.0 { area / width } else { 0.0 };
+            out[*idx] = Rect::new(rect.x, y, width, height);
+            y += height;
+        }
+        rect.x += width;
+        rect.width -= width;
+    } else {
+        let height = if rect.width > 0.0 {
+            sum / rect.width
+        } else {
+            0.0
+        };
+        let mut x = rect.x;
+        for (idx, area) in row {
+            let width = if height > 0.0 { area / height } else { 0.0 };
+            out[*idx] = Rect::new(x, rect.y, width, height);
+            x += width;
+        }
+        rect.y += height;
+        rect.height -= height;
+    }
+}
+
+// Squarified treemap of the `items` (index, area), sorted by decreasing area
+fn squarify(items: &[(usize, f64)], mut rect: Rect, out: &mut [Rect]) {
+    let mut start = 0;
+    while start < items.len() {
+        let side = rect.width.min(rect.height);
+        let mut end = start + 1;
+        while end < items.len()
+            && worst(&items[start..=end], side) <= worst(&items[start..end], side)
+        {
+            end += 1;
+        }
+        layout_row(&items[start..end], &mut rect, out);
+        start = end;
+    }
+}
+
+impl<T: Debug> Tree<T> {
+    /// Split `area` in a squarified treemap, where each node gets a [Rect] inside
+    /// the one of its parent, in pre-order.
+    ///
+    /// The leaves are sized by their `weight`, and the other nodes by the sum of
+    /// the weights of their children, so the children tile the area of the parent.
+    pub fn treemap_layout(&self, weight: impl Fn(&T) -> f64, area: Rect) -> Vec<(NodeId, Rect)> {
+        let mut weights = vec![0.0; self.len()];
+        for pos in (0..self.len()).rev() {
+            if self._is_leaf(pos) {
+                weights[pos] = weight(&self.data[pos]).max(0.0);
+            }
+            if pos > 0 {
+                weights[self.parent[pos]] += weights[pos];
+            }
+        }
+
+        let mut rects = vec![Rect::new(area.x, area.y, 0.0, 0.0); self.len()];
+        rects[0] = area;
+        for pos in 0..self.len() {
+            let rect = rects[pos];
+            let scale = if weights[pos] > 0.0 {
+                rect.area() / weights[pos]
+            } else {
+                0.0
+            };
+            let mut items: Vec<_> = self
+                ._direct_children(pos)
+                .map(|child| (child, weights[child] * scale))
+                .collect();
+            // Without weight the rect is empty, at the corner of the parent
+            items.retain(|(child, area)| {
+                if *area > 0.0 {
+                    true
+                } else {
+                    rects[*child] = Rect::new(rect.x, rect.y, 0.0, 0.0);
+                    false
+                }
+            });
+            items.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+            squarify(&items, rect, &mut rects);
+        }
+
+        rects
+            .into_iter()
+            .enumerate()
+            .map(|(pos, rect)| (pos.into(), rect))
+            .collect()
+    }
+}
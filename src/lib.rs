@@ -17,10 +17,10 @@
 //! ```bash
 //! . Users
 //! ├── jhon_doe
-//! ├   ├── file1.rs
-//! ├   ├── file2.rs
-//! ├── jane_doe
-//! └────── cat.jpg
+//! │   ├── file1.rs
+//! │   └── file2.rs
+//! └── jane_doe
+//!     └── cat.jpg
 //! ```
 //!
 //! ... flattened in pre-order on 3 [Vec], that store the data, the level/deep and the parent:
@@ -79,18 +79,42 @@
 //! > “High-performance Tree Wrangling, the APL Way”
 //! > -- <cite> [Aaron Hsu - APL Wiki](https://aplwiki.com/wiki/Aaron_Hsu)  
 
+/// Flat-tree cursors
+pub mod cursor;
+/// Flat-tree errors
+pub mod error;
+/// Flat-tree macros
+#[macro_use]
+mod macros;
 /// Flat-tree iterators
 pub mod iter;
+/// Flat-tree layouts
+pub mod layout;
+/// Flat-tree nested serde representation
+#[cfg(feature = "serde-nested")]
+pub mod nested;
 /// Flat-tree nodes
 pub mod node;
+#[cfg(feature = "rayon")]
+mod par;
+#[cfg(feature = "serde")]
+mod serde_impl;
+/// Flat-tree side-tables
+pub mod store;
 #[cfg(test)]
 mod tests;
 /// Flat-tree implementation
 pub mod tree;
+/// Flat-tree borrowed views
+pub mod view;
 /// Import this module for easy access to the Flat-tree
 pub mod prelude {
+    pub use crate::cursor::{Cursor, DfsCursor};
+    pub use crate::error::{TreeError, ValidationError};
     pub use crate::iter;
     pub use crate::node::{Node, NodeId, NodeMut, TreeMut};
+    pub use crate::store::NodeStore;
     pub use crate::tree;
-    pub use crate::tree::Tree;
+    pub use crate::tree::{IndentCache, LevelIndex, Prune, Tree, TreeEvent};
+    pub use crate::view::{TreeView, ViewNode};
 }
@@ -0,0 +1,45 @@
+/// Build a [crate::tree::Tree] from a literal, in pre-order.
+///
+/// Each node is `data` or `data => { children, ... }`.
+///
+/// # Examples
+/// ```
+/// use tree_flat::prelude::*;
+///
+/// let tree = tree!("Users" => {
+///     "jhon_doe" => { "file1.rs", "file2.rs" },
+///     "jane_doe" => { "cat.jpg" },
+/// });
+///
+/// assert_eq!(tree.as_level(), [0, 1, 2, 2, 1, 2,]);
+/// assert_eq!(tree.as_parents(), [0, 0, 1, 1, 0, 4,]);
+/// ```
+#[macro_export]
+macro_rules! tree {
+    ($root:expr $(=> { $($children:tt)* })?) => {{
+        let mut tree = $crate::tree::Tree::new($root);
+        {
+            #[allow(unused_mut, unused_variables)]
+            let mut parent = tree.tree_root_mut();
+            $($crate::__tree_children!(parent; $($children)*);)?
+        }
+        tree
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __tree_children {
+    ($parent:ident;) => {};
+    ($parent:ident; $data:expr => { $($children:tt)* } $(, $($rest:tt)*)?) => {
+        {
+            let mut child = $parent.push($data);
+            $crate::__tree_children!(child; $($children)*);
+        }
+        $crate::__tree_children!($parent; $($($rest)*)?);
+    };
+    ($parent:ident; $data:expr $(, $($rest:tt)*)?) => {
+        $parent.push($data);
+        $crate::__tree_children!($parent; $($($rest)*)?);
+    };
+}
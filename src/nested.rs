@@ -0,0 +1,132 @@
+//! The nested `{value, children}` serde representation of a [crate::tree::Tree].
+//!
+//! Use it on a field with `#[serde(with = "tree_flat::nested")]`, or wrap the
+//! tree in [crate::nested::Nested]:
+//!
+//! ```
+//! use tree_flat::prelude::*;
+//! use tree_flat::nested::Nested;
+//!
+//! let tree = tree!(1 => { 2, 3 });
+//! let json = serde_json::to_string(&Nested(&tree)).unwrap();
+//! assert_eq!(
+//!     json,
+//!     r#"{"value":1,"children":[{"value":2,"children":[]},{"value":3,"children":[]}]}"#
+//! );
+//! let Nested(back): Nested<Tree<i32>> = serde_json::from_str(&json).unwrap();
+//! assert_eq!(back, tree);
+//! ```
+use std::fmt::Debug;
+
+use serde::ser::{SerializeSeq, SerializeStruct};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::prelude::*;
+
+/// Wrapper to (de)serialize a [Tree] with the nested representation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Nested<T>(pub T);
+
+// A node and its subtree, serialized without building the nested tree
+struct NodeRef<'a, T> {
+    tree: &'a Tree<T>,
+    idx: usize,
+}
+
+struct ChildrenRef<'a, T> {
+    tree: &'a Tree<T>,
+    idx: usize,
+}
+
+impl<T: Debug + Serialize> Serialize for NodeRef<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut node = serializer.serialize_struct("Node", 2)?;
+        node.serialize_field("value", &self.tree.data[self.idx])?;
+        node.serialize_field(
+            "children",
+            &ChildrenRef {
+                tree: self.tree,
+                idx: self.idx,
+            },
+        )?;
+        node.end()
+    }
+}
+
+impl<T: Debug + Serialize> Serialize for ChildrenRef<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let len = self.tree._direct_children(self.idx).count();
+        let mut seq = serializer.serialize_seq(Some(len))?;
+        for idx in self.tree._direct_children(self.idx) {
+            seq.serialize_element(&NodeRef {
+                tree: self.tree,
+                idx,
+            })?;
+        }
+        seq.end()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Node")]
+struct NestedNode<T> {
+    value: T,
+    #[serde(default = "Vec::new")]
+    children: Vec<NestedNode<T>>,
+}
+
+/// Serialize the [Tree] as nested `{value, children}` nodes, starting at the root.
+pub fn serialize<T, S>(tree: &Tree<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Debug + Serialize,
+    S: Serializer,
+{
+    NodeRef { tree, idx: 0 }.serialize(serializer)
+}
+
+/// Deserialize a [Tree] from nested `{value, children}` nodes, flattening them
+/// in pre-order. A missing `children` is a leaf.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Tree<T>, D::Error>
+where
+    T: Debug + Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    let root = NestedNode::deserialize(deserializer)?;
+    let mut tree = Tree::new(root.value);
+    // (children left, their parent) per open node, reversed to pop in order
+    let mut stack = vec![(root.children.into_iter().rev().collect::<Vec<_>>(), 0)];
+    while let Some((children, parent)) = stack.last_mut() {
+        match children.pop() {
+            Some(node) => {
+                let parent = *parent;
+                let idx = tree.len();
+                tree.data.push(node.value);
+                tree.level.push(tree.level[parent] + 1);
+                tree.parent.push(parent);
+                stack.push((node.children.into_iter().rev().collect(), idx));
+            }
+            None => {
+                stack.pop();
+            }
+        }
+    }
+    Ok(tree)
+}
+
+impl<T: Debug + Serialize> Serialize for Nested<&Tree<T>> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize(self.0, serializer)
+    }
+}
+
+impl<T: Debug + Serialize> Serialize for Nested<Tree<T>> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de, T: Debug + Deserialize<'de>> Deserialize<'de> for Nested<Tree<T>> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize(deserializer).map(Nested)
+    }
+}
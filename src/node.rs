@@ -1,5 +1,6 @@
 use std::fmt::{Debug, Display, Formatter};
 use std::num::NonZeroUsize;
+use std::ops::Range;
 
 use crate::iter::*;
 use crate::prelude::*;
@@ -14,6 +15,15 @@ use crate::prelude::*;
 pub struct NodeId(NonZeroUsize);
 
 impl NodeId {
+    /// The [NodeId] of the root, always the first node.
+    ///
+    /// The root is stored as its own parent, but it has no parent: see
+    /// [Node::parent_id].
+    pub const ROOT: NodeId = match NonZeroUsize::new(1) {
+        Some(id) => NodeId(id),
+        None => unreachable!(),
+    };
+
     pub fn from_index(n: usize) -> Self {
         NodeId(NonZeroUsize::new(n + 1).unwrap())
     }
@@ -21,6 +31,19 @@ impl NodeId {
     pub fn to_index(self) -> usize {
         self.0.get() - 1
     }
+
+    /// Check if this is the [Self::ROOT].
+    pub fn is_root(&self) -> bool {
+        *self == NodeId::ROOT
+    }
+
+    /// Check if this [NodeId] comes before `other` in the pre-order of the tree,
+    /// so it is an ancestor of it or is in an earlier branch.
+    ///
+    /// A [NodeId] doesn't precede itself.
+    pub fn precedes(&self, other: NodeId) -> bool {
+        self.to_index() < other.to_index()
+    }
 }
 
 impl Display for NodeId {
@@ -42,7 +65,10 @@ impl From<NodeId> for usize {
 }
 
 /// An immutable view of the [Self::data] in the [Tree] with their [NodeId].
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+///
+/// Two [Node] are equal (and ordered) by their `id` & `data`, without looking
+/// at the [Tree] containing them, so nodes of cloned trees are equal.
+#[derive(Clone, Copy)]
 pub struct Node<'a, T: 'a> {
     /// Node ID.
     pub id: NodeId,
@@ -52,28 +78,65 @@ pub struct Node<'a, T: 'a> {
     pub(crate) tree: &'a Tree<T>,
 }
 
-impl<T: Debug> Node<'_, T> {
+impl<'a, T: Debug> Node<'a, T> {
     pub fn level(&self) -> usize {
         self.tree.level[self.id.to_index()]
     }
+    /// The index of the parent of this [Node].
+    ///
+    /// The root is stored as its own parent, so it returns 0. Use
+    /// [Self::parent_id] to tell the root apart.
     pub fn parent(&self) -> usize {
         self.tree.parent[self.id.to_index()]
     }
 
-    /// An [Iterator] of the parents from this [Node].
-    pub fn parents(&self) -> ParentIter<'_, T> {
+    /// The [NodeId] of the parent of this [Node], or [None] for the root.
+    pub fn parent_id(&self) -> Option<NodeId> {
+        if self.id.is_root() {
+            None
+        } else {
+            Some(self.parent().into())
+        }
+    }
+
+    /// The data of the parent of this [Node], or [None] for the root.
+    pub fn parent_data(&self) -> Option<&'a T> {
+        self.parent_id().map(|id| &self.tree.data[id.to_index()])
+    }
+
+    /// An [Iterator] of the parents from this [Node], up to the root.
+    ///
+    /// Use `.rev()` to get them from the root down.
+    pub fn parents(&self) -> ParentIter<'a, T> {
         ParentIter {
-            parent: self.parent(),
             node: self.id,
+            back: 0,
+            stack: Vec::new(),
             tree: self.tree,
         }
     }
 
+    /// An [Iterator] of the parents from this [Node], paired with their level.
+    pub fn ancestors_with_level(&self) -> AncestorsWithLevel<'a, T> {
+        self.parents().map(|x| {
+            let level = x.level();
+            (x, level)
+        })
+    }
+
     /// An [Iterator] of the children from this [Node].
     pub fn children(&self) -> ChildrenIter<'_, T> {
         ChildrenIter::new(self.id, self.tree)
     }
 
+    /// An [Iterator] of the subtree of this [Node] in pre-order: itself, then
+    /// all its descendants.
+    pub fn subtree(&self) -> impl Iterator<Item = Node<'a, T>> {
+        let tree = self.tree;
+        let idx = self.id.to_index();
+        (idx..tree._subtree_end(idx)).map(move |x| tree._make_node(x.into()))
+    }
+
     /// An [Iterator] of the siblings from this [Node].
     pub fn siblings(&self) -> SiblingsIter<'_, T> {
         SiblingsIter {
@@ -83,6 +146,176 @@ impl<T: Debug> Node<'_, T> {
             tree: self.tree,
         }
     }
+
+    /// Check if this [Node] has no children.
+    pub fn is_leaf(&self) -> bool {
+        self.tree._is_leaf(self.id.to_index())
+    }
+
+    /// The next [Node] in pre-order, ie: the one at `self.id + 1`.
+    ///
+    /// Comparing the levels tells if the tree is descending, staying or ascending.
+    pub fn next_in_preorder(&self) -> Option<Node<'a, T>> {
+        self.tree.node((self.id.to_index() + 1).into())
+    }
+
+    /// The descendants of this [Node] grouped by their level, starting with the
+    /// direct children.
+    pub fn descendants_by_level(&self) -> Vec<Vec<Node<'a, T>>> {
+        let idx = self.id.to_index();
+        let base = self.level() + 1;
+        let mut groups: Vec<Vec<_>> = Vec::new();
+        for pos in idx + 1..self.tree._subtree_end(idx) {
+            let deep = self.tree.level[pos] - base;
+            if groups.len() <= deep {
+                groups.resize_with(deep + 1, Vec::new);
+            }
+            groups[deep].push(self.tree._make_node(pos.into()));
+        }
+        groups
+    }
+
+    /// Clear `buf` and fill it with the ids of the *direct* children, so the
+    /// buffer can be reused without allocating on each call.
+    pub fn children_ids_into(&self, buf: &mut Vec<NodeId>) {
+        buf.clear();
+        buf.extend(
+            self.tree
+                ._direct_children(self.id.to_index())
+                .map(NodeId::from_index),
+        );
+    }
+
+    /// The lowest common ancestor of this [Node] and `other`, see [Tree::lca].
+    pub fn common_ancestor_with(&self, other: NodeId) -> Option<Node<'a, T>> {
+        self.tree.lca(self.id, other)
+    }
+
+    /// An [Iterator] of the siblings that share the parent of this [Node], in pre-order.
+    ///
+    /// Unlike [Self::siblings], the nodes of the same level under other parents are
+    /// not included.
+    pub fn siblings_same_parent(&self) -> impl Iterator<Item = Node<'a, T>> {
+        let tree = self.tree;
+        let idx = self.id.to_index();
+        let children = if idx > 0 {
+            Some(tree._direct_children(self.parent()))
+        } else {
+            None
+        };
+        children
+            .into_iter()
+            .flatten()
+            .filter(move |x| *x != idx)
+            .map(move |x| tree._make_node(x.into()))
+    }
+
+    /// Like [Self::siblings_same_parent], but including this [Node] in his
+    /// position, so it yields all the children of the parent. The root only
+    /// yields itself.
+    pub fn siblings_with_self(&self) -> impl Iterator<Item = Node<'a, T>> {
+        let tree = self.tree;
+        let mut this = Some(self.id);
+        let mut rest = self.siblings_same_parent().peekable();
+        std::iter::from_fn(move || match (this, rest.peek()) {
+            (Some(id), Some(next)) if next.id < id => rest.next(),
+            (Some(id), _) => {
+                this = None;
+                Some(tree._make_node(id))
+            }
+            (None, _) => rest.next(),
+        })
+    }
+
+    /// The number of [Self::siblings_same_parent], without building the nodes.
+    pub fn num_siblings_same_parent(&self) -> usize {
+        let idx = self.id.to_index();
+        if idx > 0 {
+            self.tree._direct_children(self.parent()).count() - 1
+        } else {
+            0
+        }
+    }
+
+    /// The number of [Self::siblings], the other nodes at the same level, without
+    /// building the nodes.
+    pub fn num_siblings_same_level(&self) -> usize {
+        let level = self.level();
+        self.tree.level.iter().filter(|x| **x == level).count() - 1
+    }
+
+    /// The siblings that share the parent of this [Node], split in the ones
+    /// before & after it.
+    pub fn siblings_partition(&self) -> (Vec<Node<'a, T>>, Vec<Node<'a, T>>) {
+        self.siblings_same_parent().partition(|x| x.id < self.id)
+    }
+
+    /// The `n`th (0-based) *direct* child of this [Node].
+    pub fn child(&self, n: usize) -> Option<Node<'a, T>> {
+        self.tree
+            ._direct_children(self.id.to_index())
+            .nth(n)
+            .map(|idx| self.tree._make_node(idx.into()))
+    }
+
+    /// The range of indices of the *direct* children of this [Node], if all of
+    /// them are leaves so they are contiguous, like the files of a directory.
+    ///
+    /// Returns [None] if some children have their own descendants in between.
+    /// A leaf returns an empty range.
+    pub fn children_contiguous_range(&self) -> Option<Range<usize>> {
+        let idx = self.id.to_index();
+        let end = self.tree._subtree_end(idx);
+        let level = self.level() + 1;
+        if self.tree.level[idx + 1..end].iter().all(|x| *x == level) {
+            Some(idx + 1..end)
+        } else {
+            None
+        }
+    }
+
+    /// The first leaf of the subtree of this [Node], following the first children
+    /// down, or itself if it is a leaf.
+    ///
+    /// The first child is always the next node in pre-order, so it is the first
+    /// leaf after this [Node].
+    pub fn leftmost_leaf(&self) -> Node<'a, T> {
+        let mut idx = self.id.to_index();
+        while !self.tree._is_leaf(idx) {
+            idx += 1;
+        }
+        self.tree._make_node(idx.into())
+    }
+
+    /// The last leaf of the subtree of this [Node], following the last children
+    /// down, or itself if it is a leaf.
+    ///
+    /// Is the last node of the subtree in pre-order, so its index + 1 is the end
+    /// of the subtree span.
+    pub fn rightmost_leaf(&self) -> Node<'a, T> {
+        let end = self.tree._subtree_end(self.id.to_index());
+        self.tree._make_node((end - 1).into())
+    }
+}
+
+impl<T: PartialEq> PartialEq for Node<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && self.data == other.data
+    }
+}
+
+impl<T: Eq> Eq for Node<'_, T> {}
+
+impl<T: PartialOrd> PartialOrd for Node<'_, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        (self.id, self.data).partial_cmp(&(other.id, other.data))
+    }
+}
+
+impl<T: Ord> Ord for Node<'_, T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.id, self.data).cmp(&(other.id, other.data))
+    }
 }
 
 impl<T: Debug> Debug for Node<'_, T> {
@@ -136,7 +369,7 @@ impl<'a, T: Debug + 'a> TreeMut<'a, T> {
 
     /// Create a new [Node<T>], record the parent & the loop, and continue to
     /// return [NodeMut<T>] so you can add more in a builder pattern
-    pub fn push(&mut self, data: T) -> TreeMut<T>
+    pub fn push(&mut self, data: T) -> TreeMut<'_, T>
     where
         T: Debug,
     {
@@ -154,4 +387,42 @@ impl<'a, T: Debug + 'a> TreeMut<'a, T> {
 
         self.tree.push_with_level(data, level, self.parent)
     }
+
+    /// Create a new [`Node<T>`] like [Self::append], and return both the created
+    /// [NodeId] & a [`NodeMut<T>`] to keep editing the data
+    pub fn push_returning(&mut self, data: T) -> (NodeId, NodeMut<'_, T>) {
+        let id = self.append(data);
+        (id, self.tree._make_node_mut(id))
+    }
+
+    /// Get a [`TreeMut<T>`] of the first *direct* child that match `pred`, or
+    /// push a new one with the data of `default`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a new child is needed but the subtree of this node is not at
+    /// the end of the tree, because it will break the pre-order.
+    pub fn get_or_insert_child(
+        &mut self,
+        pred: impl Fn(&T) -> bool,
+        default: impl FnOnce() -> T,
+    ) -> TreeMut<'_, T> {
+        let parent = self.parent.to_index();
+        let found = self
+            .tree
+            ._direct_children(parent)
+            .find(|idx| pred(&self.tree.data[*idx]));
+
+        let id = match found {
+            Some(idx) => idx.into(),
+            None => {
+                assert!(
+                    self.tree.can_push_child(self.parent),
+                    "the new child will not be in pre-order"
+                );
+                self.append(default())
+            }
+        };
+        self.tree._make_tree_mut(id, id)
+    }
 }
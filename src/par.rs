@@ -0,0 +1,107 @@
+use std::fmt::Debug;
+
+use rayon::prelude::*;
+
+use crate::prelude::*;
+use crate::tree::level_jump;
+
+// The nodes of each chunk processed in parallel
+const CHUNK: usize = 1 << 14;
+
+// The parents of a chunk of `level` that starts at `start`.
+//
+// A parent before the chunk is unknown yet, so the position is returned to be
+// resolved later, with the stack of the last node per level at the end of the chunk.
+struct Partial {
+    parent: Vec<usize>,
+    unresolved: Vec<usize>,
+    stack: Vec<(usize, usize)>,
+}
+
+fn partial_parents(level: &[usize], start: usize) -> Partial {
+    let mut parent = Vec::with_capacity(level.len());
+    let mut unresolved = Vec::new();
+    // (level, index) of the last node seen at each level, deeper at the top
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+    for (pos, of) in level.iter().enumerate() {
+        while matches!(stack.last(), Some((l, _)) if l >= of) {
+            stack.pop();
+        }
+        match stack.last() {
+            Some((_, idx)) => parent.push(*idx),
+            None => {
+                parent.push(0);
+                unresolved.push(pos);
+            }
+        }
+        stack.push((*of, start + pos));
+    }
+    Partial {
+        parent,
+        unresolved,
+        stack,
+    }
+}
+
+impl<T: Debug + Send> Tree<T> {
+    /// Like [Tree::from_levels], but checking the levels & computing the parents
+    /// in parallel chunks, for very large inputs.
+    ///
+    /// Inside a chunk, the parents only need the nodes before them in the same
+    /// chunk. The rest are resolved after, with the last node of each level at
+    /// the end of the previous chunks.
+    ///
+    /// # Errors
+    ///
+    /// The same of [Tree::from_levels].
+    pub fn from_levels_par(data: Vec<T>, level: Vec<usize>) -> Result<Tree<T>, TreeError> {
+        if data.is_empty() {
+            return Err(TreeError::Empty);
+        }
+        if level.len() != data.len() {
+            return Err(TreeError::LengthMismatch);
+        }
+        if level[0] != 0 {
+            return Err(TreeError::InvalidLevel(0.into()));
+        }
+        let jump = level
+            .par_chunks(CHUNK)
+            .enumerate()
+            .find_map_first(|(n, chunk)| {
+                let start = n * CHUNK;
+                level_jump(&level, start.max(1)..start + chunk.len())
+            });
+        if let Some(pos) = jump {
+            return Err(TreeError::InvalidLevel(pos.into()));
+        }
+
+        let chunks: Vec<_> = level
+            .par_chunks(CHUNK)
+            .enumerate()
+            .map(|(n, chunk)| partial_parents(chunk, n * CHUNK))
+            .collect();
+
+        let mut parent = Vec::with_capacity(level.len());
+        // The last node seen at each level, at the end of the previous chunks
+        let mut last: Vec<usize> = Vec::new();
+        for (n, mut chunk) in chunks.into_iter().enumerate() {
+            for pos in chunk.unresolved {
+                let of = level[n * CHUNK + pos];
+                if of > 0 {
+                    chunk.parent[pos] = last[of - 1];
+                }
+            }
+            if let Some((of, _)) = chunk.stack.first() {
+                last.truncate(*of);
+                last.extend(chunk.stack.iter().map(|(_, idx)| *idx));
+            }
+            parent.append(&mut chunk.parent);
+        }
+
+        Ok(Tree {
+            data,
+            level,
+            parent,
+        })
+    }
+}
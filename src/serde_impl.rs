@@ -0,0 +1,45 @@
+use std::convert::TryFrom;
+use std::fmt::Debug;
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::prelude::*;
+
+// The columnar representation: the three vectors, as stored
+#[derive(Serialize)]
+struct ColumnsRef<'a, T> {
+    data: &'a [T],
+    level: &'a [usize],
+    parent: &'a [usize],
+}
+
+#[derive(Deserialize)]
+struct Columns<T> {
+    data: Vec<T>,
+    level: Vec<usize>,
+    parent: Vec<usize>,
+}
+
+/// Serialize the [Tree] as the columnar `{data, level, parent}` representation.
+///
+/// For the nested `{value, children}` one, see [crate::nested].
+impl<T: Debug + Serialize> Serialize for Tree<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ColumnsRef {
+            data: &self.data,
+            level: &self.level,
+            parent: &self.parent,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Deserialize the [Tree] from the columnar `{data, level, parent}` representation,
+/// rejecting inconsistent columns like [Tree::validate].
+impl<'de, T: Debug + Deserialize<'de>> Deserialize<'de> for Tree<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let columns = Columns::deserialize(deserializer)?;
+        Tree::try_from((columns.data, columns.level, columns.parent)).map_err(D::Error::custom)
+    }
+}
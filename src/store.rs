@@ -0,0 +1,81 @@
+use std::fmt::Debug;
+use std::ops::{Index, IndexMut};
+
+use crate::prelude::*;
+
+/// A side-table of values indexed by [NodeId], aligned with a [Tree] of the same length.
+///
+/// It attaches computed attributes (colors, sizes, ...) to the nodes, without
+/// storing them in the data of the [Tree].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NodeStore<V> {
+    pub(crate) values: Vec<V>,
+}
+
+impl<V> NodeStore<V> {
+    /// Create a new [NodeStore] from the values, in pre-order.
+    pub fn from_vec(values: Vec<V>) -> Self {
+        NodeStore { values }
+    }
+
+    /// Returns the number of values in the store.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if the store contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Get the value of the [NodeId]
+    pub fn get(&self, id: NodeId) -> Option<&V> {
+        self.values.get(id.to_index())
+    }
+
+    /// Get the mutable value of the [NodeId]
+    pub fn get_mut(&mut self, id: NodeId) -> Option<&mut V> {
+        self.values.get_mut(id.to_index())
+    }
+
+    /// A slice view of the values
+    pub fn as_slice(&self) -> &[V] {
+        &self.values
+    }
+}
+
+impl<V: Default> NodeStore<V> {
+    /// Set the value of the [NodeId], returning the old one.
+    ///
+    /// If the [NodeId] is past the end, the store grows with [Default] values.
+    pub fn insert(&mut self, id: NodeId, value: V) -> V {
+        let idx = id.to_index();
+        if idx >= self.values.len() {
+            self.values.resize_with(idx + 1, V::default);
+        }
+        std::mem::replace(&mut self.values[idx], value)
+    }
+}
+
+impl<V> Index<NodeId> for NodeStore<V> {
+    type Output = V;
+
+    fn index(&self, id: NodeId) -> &Self::Output {
+        &self.values[id.to_index()]
+    }
+}
+
+impl<V> IndexMut<NodeId> for NodeStore<V> {
+    fn index_mut(&mut self, id: NodeId) -> &mut Self::Output {
+        &mut self.values[id.to_index()]
+    }
+}
+
+impl<T: Debug> Tree<T> {
+    /// Create a [NodeStore] with a [Default] value for each node.
+    pub fn new_store<V: Default>(&self) -> NodeStore<V> {
+        let mut values = Vec::with_capacity(self.len());
+        values.resize_with(self.len(), V::default);
+        NodeStore { values }
+    }
+}
@@ -3,19 +3,19 @@ use crate::prelude::*;
 // This is the tree used for the tests:
 // . 0
 // ├── 1
-// ├   ├── 2
+// │   └── 2
 // ├── 3
-// ├   ├── 4
-// ├   ├   ├── 5
-// ├   ├── 6
-// ├── 7
-// ├   ├── 8
-// ├   ├   ├── 9
-// ├   ├   ├── 10
-// ├   ├── 11
-// ├   ├   ├── 12
-// ├   ├   ├── 13
-// └────── 14
+// │   ├── 4
+// │   │   └── 5
+// │   └── 6
+// └── 7
+//     ├── 8
+//     │   ├── 9
+//     │   └── 10
+//     ├── 11
+//     │   ├── 12
+//     │   └── 13
+//     └── 14
 fn build() -> Tree<i32> {
     let mut tree = Tree::with_capacity(0, 15);
 
@@ -119,7 +119,7 @@ fn iter() {
     let tree = build();
     let mut data = Vec::with_capacity(tree.len());
 
-    for x in tree.into_iter() {
+    for x in tree.into_iter_ref() {
         data.push(*x.data);
     }
 
@@ -209,3 +209,1836 @@ fn siblings() {
     let siblings = make_siblings(&tree, 10);
     assert_eq!(&[5, 9, 12, 13], siblings.as_slice());
 }
+
+#[test]
+fn dedup_children() {
+    let mut tree = Tree::new("root");
+    let mut root = tree.tree_root_mut();
+    root.push("a").push("x");
+    root.push("b");
+    root.push("a").push("y");
+    root.push("c");
+
+    tree.dedup_children(0.into());
+
+    assert_eq!(tree.as_data(), ["root", "a", "x", "b", "c"]);
+    assert_eq!(tree.as_level(), [0, 1, 2, 1, 1]);
+    assert_eq!(tree.as_parents(), [0, 0, 1, 0, 0]);
+}
+
+#[test]
+fn zip() {
+    let tree = build();
+    let names: Vec<String> = tree.as_data().iter().map(|x| format!("n{}", x)).collect();
+    let mut other = Tree::new(names[0].clone());
+    for (pos, name) in names.iter().enumerate().skip(1) {
+        other.push_with_level(name.clone(), tree.level[pos], tree.parent[pos].into());
+    }
+
+    let pairs: Vec<_> = tree.zip(&other).unwrap().collect();
+    assert_eq!(pairs.len(), tree.len());
+    assert_eq!(pairs[7], (&7, &"n7".to_string()));
+
+    other.pop();
+    assert!(tree.zip(&other).is_none());
+    assert!(tree.zip(&Tree::new(0)).is_none());
+}
+
+#[test]
+fn diff() {
+    use crate::tree::TreeDiff;
+
+    let tree = build();
+    assert!(tree.diff(&tree.clone()).is_empty());
+
+    let mut other = tree.clone();
+    *other.node_mut(5.into()).unwrap().data = 50;
+    assert_eq!(tree.diff(&other), [TreeDiff::Changed(5.into())]);
+
+    other.pop();
+    assert_eq!(
+        tree.diff(&other),
+        [TreeDiff::Changed(5.into()), TreeDiff::Removed(14.into())]
+    );
+    assert_eq!(
+        other.diff(&tree),
+        [TreeDiff::Changed(5.into()), TreeDiff::Added(14.into())]
+    );
+}
+
+#[test]
+fn child() {
+    let tree = build();
+    let node = tree.node(7.into()).unwrap();
+    assert_eq!(node.child(0).unwrap().id, 8.into());
+    assert_eq!(node.child(1).unwrap().id, 11.into());
+    assert_eq!(node.child(2).unwrap().id, 14.into());
+    assert!(node.child(3).is_none());
+    assert!(tree.node(14.into()).unwrap().child(0).is_none());
+}
+
+#[test]
+fn in_order() {
+    // . 4
+    // ├── 2
+    // │   ├── 1
+    // │   └── 3
+    // └── 6
+    //     ├── 5
+    //     └── 7
+    let mut tree = Tree::new(4);
+    let mut root = tree.tree_root_mut();
+    let mut left = root.push(2);
+    left.push(1);
+    left.push(3);
+    let mut right = root.push(6);
+    right.push(5);
+    right.push(7);
+
+    let data: Vec<_> = tree.in_order().map(|x| *x.data).collect();
+    assert_eq!(data, [1, 2, 3, 4, 5, 6, 7]);
+}
+
+#[test]
+fn root_data() {
+    let mut tree = build();
+    assert_eq!(tree.root_data(), &0);
+    *tree.root_data_mut() = 100;
+    assert_eq!(tree.root_data(), &100);
+    assert_eq!(tree.root().data, &100);
+}
+
+#[test]
+fn iter_max_depth() {
+    let tree = build();
+    let ids: Vec<_> = tree.iter_max_depth(1).map(|x| x.id.to_index()).collect();
+    assert_eq!(ids, [0, 1, 3, 7]);
+
+    let ids: Vec<_> = tree.iter_max_depth(0).map(|x| x.id.to_index()).collect();
+    assert_eq!(ids, [0]);
+    assert_eq!(tree.iter_max_depth(10).count(), tree.len());
+}
+
+#[test]
+fn collapse() {
+    let mut tree = build();
+    tree.collapse(8.into(), |parent, child| *parent += child);
+
+    assert_eq!(
+        tree.as_data(),
+        [0, 1, 2, 3, 4, 5, 6, 15, 9, 10, 11, 12, 13, 14]
+    );
+    assert_eq!(tree.as_level(), [0, 1, 2, 1, 2, 3, 2, 1, 2, 2, 2, 3, 3, 2]);
+    assert_eq!(
+        tree.as_parents(),
+        [0, 0, 1, 0, 3, 4, 3, 0, 7, 7, 7, 10, 10, 7]
+    );
+
+    tree.collapse(3.into(), |_, _| {});
+    assert_eq!(
+        tree.as_data(),
+        [0, 1, 2, 4, 5, 6, 15, 9, 10, 11, 12, 13, 14]
+    );
+    assert_eq!(tree.as_level(), [0, 1, 2, 1, 2, 1, 1, 2, 2, 2, 3, 3, 2]);
+    assert_eq!(tree.as_parents(), [0, 0, 1, 0, 3, 0, 0, 6, 6, 6, 9, 9, 6]);
+}
+
+#[test]
+fn promote() {
+    let mut tree = build();
+    // Not the last child of 8
+    assert!(!tree.promote(9.into()));
+    // Child of the root
+    assert!(!tree.promote(7.into()));
+    assert!(!tree.promote(0.into()));
+    assert_eq!(tree, build());
+
+    assert!(tree.promote(13.into()));
+    assert_eq!(tree.as_level()[11..], [2, 3, 2, 2]);
+    assert_eq!(tree.as_parents()[11..], [7, 11, 7, 7]);
+
+    // Now 13 & 14 are after it
+    assert!(!tree.promote(11.into()));
+    assert!(tree.promote(14.into()));
+    assert_eq!(tree.as_level()[11..], [2, 3, 2, 1]);
+    assert_eq!(tree.as_parents()[11..], [7, 11, 7, 0]);
+}
+
+#[test]
+fn subtree_len() {
+    let tree = build();
+    assert_eq!(tree.subtree_len(0.into()), 15);
+    assert_eq!(tree.subtree_len(3.into()), 4);
+    assert_eq!(tree.subtree_len(7.into()), 8);
+    assert_eq!(tree.subtree_len(14.into()), 1);
+}
+
+#[test]
+fn from_parent_map() {
+    use std::collections::HashMap;
+
+    let mut map = HashMap::new();
+    map.insert(1, ("root", 1));
+    map.insert(2, ("a", 1));
+    map.insert(3, ("b", 2));
+    map.insert(4, ("c", 3));
+
+    let tree = Tree::from_parent_map(1, map.clone()).unwrap();
+    assert_eq!(tree.as_data(), ["root", "a", "b", "c"]);
+    assert_eq!(tree.as_level(), [0, 1, 2, 3]);
+    assert_eq!(tree.as_parents(), [0, 0, 1, 2]);
+
+    assert_eq!(
+        Tree::from_parent_map(10, map.clone()),
+        Err(TreeError::MissingRoot)
+    );
+
+    let mut missing = map.clone();
+    missing.insert(5, ("d", 10));
+    assert_eq!(
+        Tree::from_parent_map(1, missing),
+        Err(TreeError::MissingParent)
+    );
+
+    let mut cycle = map;
+    cycle.insert(5, ("d", 6));
+    cycle.insert(6, ("e", 5));
+    assert_eq!(Tree::from_parent_map(1, cycle), Err(TreeError::Cycle));
+}
+
+#[test]
+fn write_tree() {
+    let tree = build();
+    let mut buffer = Vec::new();
+    tree.write_tree(&mut buffer).unwrap();
+
+    assert_eq!(String::from_utf8(buffer).unwrap(), tree.to_string());
+}
+
+#[test]
+fn nodes() {
+    let tree = build();
+    let mut nodes = tree.nodes();
+    assert_eq!(nodes.len(), tree.len());
+    nodes.next();
+    assert_eq!(nodes.len(), tree.len() - 1);
+    assert_eq!(nodes.count(), tree.len() - 1);
+}
+
+#[test]
+fn next_in_preorder() {
+    let tree = build();
+    let next = tree.node(4.into()).unwrap().next_in_preorder().unwrap();
+    assert_eq!(next.id, 5.into());
+    assert!(next.level() > 2);
+
+    let next = tree.node(10.into()).unwrap().next_in_preorder().unwrap();
+    assert_eq!(next.id, 11.into());
+    assert!(next.level() < 3);
+
+    assert!(tree.node(14.into()).unwrap().next_in_preorder().is_none());
+}
+
+#[test]
+fn capacities() {
+    let mut tree = Tree::new(0);
+    tree.reserve_exact(10);
+    let (data, level, parent) = tree.capacities();
+    assert!(data >= 11);
+    assert!(level >= 11);
+    assert!(parent >= 11);
+    assert_eq!(tree.capacity(), data);
+}
+
+fn assert_impl<X: Send + Sync>() {}
+
+// If any of these don't implement `Send + Sync` when `T` does, this fail to compile
+fn _send_sync<'a, T: Send + Sync + 'a>() {
+    assert_impl::<Tree<T>>();
+    assert_impl::<Node<'a, T>>();
+    assert_impl::<NodeMut<'a, T>>();
+    assert_impl::<TreeMut<'a, T>>();
+    assert_impl::<iter::TreeIter<'a, T>>();
+    assert_impl::<iter::ParentIter<'a, T>>();
+    assert_impl::<iter::ChildrenIter<'a, T>>();
+    assert_impl::<iter::SiblingsIter<'a, T>>();
+}
+
+#[test]
+fn send_sync() {
+    _send_sync::<String>();
+    assert_impl::<NodeId>();
+}
+
+#[test]
+fn leaf_data() {
+    let tree = build();
+    assert_eq!(tree.leaf_data(), [&2, &5, &6, &9, &10, &12, &13, &14]);
+    assert!(tree.node(14.into()).unwrap().is_leaf());
+    assert!(!tree.node(11.into()).unwrap().is_leaf());
+    assert_eq!(Tree::new(0).leaf_data(), [&0]);
+}
+
+#[test]
+fn descendants_by_level() {
+    let tree = build();
+    let groups: Vec<Vec<_>> = tree
+        .node(7.into())
+        .unwrap()
+        .descendants_by_level()
+        .iter()
+        .map(|x| x.iter().map(|x| *x.data).collect())
+        .collect();
+    assert_eq!(groups, [vec![8, 11, 14], vec![9, 10, 12, 13]]);
+
+    assert!(tree
+        .node(14.into())
+        .unwrap()
+        .descendants_by_level()
+        .is_empty());
+}
+
+#[test]
+fn try_from_vectors() {
+    use std::convert::TryFrom;
+
+    let tree = build();
+    let raw = (tree.data.clone(), tree.level.clone(), tree.parent.clone());
+    assert_eq!(Tree::try_from(raw), Ok(tree));
+
+    let empty: (Vec<i32>, _, _) = (vec![], vec![], vec![]);
+    assert_eq!(Tree::try_from(empty), Err(TreeError::Empty));
+    assert_eq!(
+        Tree::try_from((vec![0, 1], vec![0, 1], vec![0])),
+        Err(TreeError::LengthMismatch)
+    );
+    assert_eq!(
+        Tree::try_from((vec![0, 1, 2], vec![0, 2, 1], vec![0, 0, 0])),
+        Err(TreeError::InvalidLevel(1.into()))
+    );
+    // 2 is a child of 1, not of the root
+    assert_eq!(
+        Tree::try_from((vec![0, 1, 2], vec![0, 1, 2], vec![0, 0, 0])),
+        Err(TreeError::InvalidParent(2.into()))
+    );
+    assert_eq!(
+        Tree::try_from((vec![0, 1], vec![0, 1], vec![1, 0])),
+        Err(TreeError::InvalidParent(0.into()))
+    );
+}
+
+#[test]
+fn skip_subtree() {
+    let tree = build();
+    let mut it = tree.iter();
+    let mut data = Vec::new();
+    while let Some(x) = it.next() {
+        data.push(*x.data);
+        if *x.data == 3 || *x.data == 7 {
+            it.skip_subtree();
+        }
+    }
+    assert_eq!(data, [0, 1, 2, 3, 7]);
+
+    let mut it = tree.iter();
+    it.skip_subtree();
+    assert_eq!(it.count(), tree.len());
+}
+
+#[test]
+fn tree_view() {
+    let tree = build();
+    let data = tree.as_data().to_vec();
+    let level = tree.as_level().to_vec();
+    let parent = tree.as_parents().to_vec();
+
+    let view = TreeView::new(&data, &level, &parent).unwrap();
+    assert_eq!(view.len(), tree.len());
+    assert_eq!(view.root().data, &0);
+
+    let all: Vec<_> = view.iter().map(|x| *x.data).collect();
+    assert_eq!(all, data);
+
+    let node = view.node(10.into()).unwrap();
+    assert_eq!(node.level(), 3);
+    let parents: Vec<_> = node.parents().map(|x| *x.data).collect();
+    assert_eq!(parents, [8, 7, 0]);
+    let siblings: Vec<_> = node.siblings().map(|x| *x.data).collect();
+    assert_eq!(siblings, [5, 9, 12, 13]);
+
+    let children: Vec<_> = view
+        .node(3.into())
+        .unwrap()
+        .children()
+        .map(|x| *x.data)
+        .collect();
+    assert_eq!(children, [4, 5, 6]);
+    assert!(view.node(15.into()).is_none());
+
+    assert_eq!(tree.as_view().as_data(), tree.as_data());
+    assert_eq!(
+        TreeView::new(&data[..2], &level, &parent).unwrap_err(),
+        TreeError::LengthMismatch
+    );
+}
+
+#[test]
+fn tree_macro() {
+    let tree = tree!(0 => {
+        1 => { 2 },
+        3 => { 4 => { 5 }, 6 },
+        7 => { 8 => { 9, 10 }, 11 => { 12, 13 }, 14 },
+    });
+    assert_eq!(tree, build());
+
+    assert_eq!(tree!("root"), Tree::new("root"));
+    assert_eq!(tree!("root" => {}), Tree::new("root"));
+}
+
+#[test]
+fn into_iter_owned() {
+    let tree = tree!("root".to_string() => { "a".to_string() => { "b".to_string() } });
+    let mut data = Vec::new();
+    for x in tree {
+        let x: String = x;
+        data.push(x);
+    }
+    assert_eq!(data, ["root", "a", "b"]);
+
+    let tree = build();
+    assert_eq!(tree.clone().into_iter().len(), tree.len());
+    assert_eq!(tree.into_iter().next_back(), Some(14));
+}
+
+#[test]
+fn tree_node_mut_push() {
+    let mut tree = Tree::new(0);
+    let child = tree.tree_root_mut().append(1);
+    tree.tree_node_mut(child).unwrap().push(2);
+
+    assert_eq!(tree.as_level(), [0, 1, 2]);
+    assert_eq!(tree.as_parents(), [0, 0, 1]);
+    assert_eq!(tree.node(2.into()).unwrap().parent(), child.to_index());
+}
+
+#[test]
+fn cursor() {
+    let mut tree = Tree::with_capacity(0, 15);
+    let mut c = tree.cursor();
+    c.down(1).down(2).up();
+    c.sibling(3).down(4).down(5).up();
+    c.sibling(6).up();
+    c.sibling(7).down(8).down(9).sibling(10).up();
+    c.sibling(11).down(12).sibling(13).up();
+    c.sibling(14);
+    assert_eq!(c.current(), 14.into());
+    c.up().up().up();
+    assert_eq!(c.current(), 0.into());
+
+    assert_eq!(tree, build());
+}
+
+#[test]
+fn get_or_insert_child() {
+    let mut tree = Tree::new("/");
+    for path in [["usr", "bin"], ["usr", "lib"]] {
+        let mut id = tree.tree_root_mut().id;
+        for part in path {
+            let mut node = tree.tree_node_mut(id).unwrap();
+            id = node.get_or_insert_child(|x| *x == part, || part).id;
+        }
+    }
+
+    assert_eq!(tree.as_data(), ["/", "usr", "bin", "lib"]);
+    assert_eq!(tree.as_level(), [0, 1, 2, 2]);
+    assert_eq!(tree.as_parents(), [0, 0, 1, 1]);
+}
+
+#[test]
+fn insert_path() {
+    let mut tree = Tree::new("/");
+    assert_eq!(tree.insert_path(["a", "b"]), Ok(2.into()));
+    assert_eq!(tree.insert_path(["a", "c"]), Ok(3.into()));
+    assert_eq!(tree.insert_path(["a"]), Ok(1.into()));
+    assert_eq!(tree.insert_path([]), Ok(0.into()));
+
+    assert_eq!(tree.as_data(), ["/", "a", "b", "c"]);
+    assert_eq!(tree.as_level(), [0, 1, 2, 2]);
+    assert_eq!(tree.as_parents(), [0, 0, 1, 1]);
+
+    assert_eq!(tree.insert_path(["x"]), Ok(4.into()));
+    let before = tree.clone();
+    assert_eq!(
+        tree.insert_path(["a", "d"]),
+        Err(TreeError::NotAtTail(1.into()))
+    );
+    assert_eq!(tree, before);
+}
+
+#[test]
+fn try_for_each_data() {
+    let tree = build();
+    let mut seen = Vec::new();
+    let result = tree.try_for_each_data(|x| {
+        seen.push(*x);
+        if *x == 4 {
+            Err(*x)
+        } else {
+            Ok(())
+        }
+    });
+    assert_eq!(result, Err(4));
+    assert_eq!(seen, [0, 1, 2, 3, 4]);
+
+    let mut sum = 0;
+    assert_eq!(
+        tree.try_for_each_data::<()>(|x| {
+            sum += x;
+            Ok(())
+        }),
+        Ok(())
+    );
+    assert_eq!(sum, tree.as_data().iter().sum::<i32>());
+}
+
+#[test]
+fn node_eq() {
+    let tree = build();
+    let mut other = tree.clone();
+    other.push_with_level(15, 1, 0.into());
+
+    assert_eq!(tree.node(5.into()), other.node(5.into()));
+    assert_ne!(tree.node(5.into()), other.node(6.into()));
+
+    *other.node_mut(5.into()).unwrap().data = 50;
+    assert_ne!(tree.node(5.into()), other.node(5.into()));
+    assert!(tree.node(5.into()) < other.node(5.into()));
+}
+
+#[test]
+fn nodes_in_range() {
+    let tree = build();
+    let data: Vec<_> = tree.nodes_in_range(5..10).map(|x| *x.data).collect();
+    assert_eq!(data, [5, 6, 7, 8, 9]);
+    assert_eq!(tree.nodes_in_range(..).count(), tree.len());
+    assert_eq!(tree.nodes_in_range(13..=14).count(), 2);
+    assert_eq!(tree.nodes_in_range(15..).count(), 0);
+}
+
+#[test]
+#[should_panic]
+fn nodes_in_range_out_of_bounds() {
+    build().nodes_in_range(10..16).count();
+}
+
+#[test]
+fn subtree_sizes() {
+    let tree = build();
+    let sizes = tree.subtree_sizes();
+    let expected: Vec<_> = tree.iter().map(|x| tree.subtree_len(x.id)).collect();
+    assert_eq!(sizes, expected);
+    assert_eq!(sizes[7], 8);
+}
+
+#[test]
+fn treemap_layout() {
+    use crate::layout::Rect;
+
+    let tree = build();
+    let area = Rect::new(0.0, 0.0, 100.0, 60.0);
+    let rects = tree.treemap_layout(|x| *x as f64, area);
+    assert_eq!(rects.len(), tree.len());
+    assert_eq!(rects[0], (0.into(), area));
+
+    let eps = 1e-6;
+    for node in tree.iter() {
+        let parent = rects[node.id.to_index()].1;
+        let children: Vec<_> = tree
+            ._direct_children(node.id.to_index())
+            .map(|x| rects[x].1)
+            .collect();
+        if children.is_empty() {
+            continue;
+        }
+        // Tile the parent...
+        let sum: f64 = children.iter().map(|x| x.area()).sum();
+        assert!((sum - parent.area()).abs() < eps, "{:?}", node);
+        for (pos, a) in children.iter().enumerate() {
+            assert!(a.x >= parent.x - eps && a.y >= parent.y - eps);
+            assert!(a.x + a.width <= parent.x + parent.width + eps);
+            assert!(a.y + a.height <= parent.y + parent.height + eps);
+            // ...without overlap
+            for b in &children[pos + 1..] {
+                let w = (a.x + a.width).min(b.x + b.width) - a.x.max(b.x);
+                let h = (a.y + a.height).min(b.y + b.height) - a.y.max(b.y);
+                assert!(w <= eps || h <= eps, "{:?} {:?}", a, b);
+            }
+        }
+    }
+    // The leaves are sized by their weight: 2 + 5 + 6 + 9 + 10 + 12 + 13 + 14 = 71
+    let scale = area.area() / 71.0;
+    assert!((rects[14].1.area() - 14.0 * scale).abs() < eps);
+    assert!((rects[3].1.area() - 11.0 * scale).abs() < eps);
+}
+
+#[test]
+fn children_ids_into() {
+    let tree = build();
+    let mut buf = Vec::new();
+
+    tree.root().children_ids_into(&mut buf);
+    assert_eq!(buf, [1.into(), 3.into(), 7.into()]);
+
+    tree.node(7.into()).unwrap().children_ids_into(&mut buf);
+    assert_eq!(buf, [8.into(), 11.into(), 14.into()]);
+
+    tree.node(14.into()).unwrap().children_ids_into(&mut buf);
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn dfs_cursor() {
+    let tree = build();
+    let mut cursor = tree.dfs_cursor();
+    let mut order = vec![*cursor.current().data];
+    let mut depths = vec![cursor.depth()];
+    loop {
+        if let Some(node) = cursor.descend() {
+            order.push(*node.data);
+            depths.push(cursor.depth());
+        } else if !cursor.ascend() {
+            break;
+        }
+    }
+    assert_eq!(order, tree.data);
+    assert_eq!(depths, tree.level);
+    assert_eq!(cursor.current().id, 0.into());
+}
+
+#[test]
+fn to_outline() {
+    let tree = build();
+    let outline = tree.to_outline("  ");
+    assert!(outline.starts_with("0\n  1\n    2\n  3\n"));
+
+    let parsed = Tree::from_indented(&outline, "  ").unwrap();
+    assert!(parsed.same_shape(&tree));
+    let data: Vec<_> = tree.as_data().iter().map(|x| x.to_string()).collect();
+    assert_eq!(parsed.as_data(), data.as_slice());
+    assert_eq!(parsed.to_outline("  "), outline);
+
+    assert_eq!(Tree::from_indented("", "  "), Err(TreeError::Empty));
+    assert_eq!(
+        Tree::from_indented("  a", "  "),
+        Err(TreeError::InvalidLevel(0.into()))
+    );
+    assert_eq!(
+        Tree::from_indented("a\n    b", "  "),
+        Err(TreeError::InvalidLevel(1.into()))
+    );
+    assert_eq!(
+        Tree::from_indented("a\nb", "  "),
+        Err(TreeError::InvalidLevel(1.into()))
+    );
+}
+
+#[test]
+fn merge() {
+    let mut tree = tree!(("/", 1) => { ("a", 1) => { ("x", 1) }, ("b", 1) });
+    let other = tree!(("/", 1) => { ("c", 1), ("a", 1) => { ("y", 1), ("x", 1) } });
+
+    tree.merge(&other, |x| x.0, |a, b| a.1 += b.1);
+
+    let expected = tree!(("/", 2) => {
+        ("a", 2) => { ("x", 2), ("y", 1) },
+        ("b", 1),
+        ("c", 1),
+    });
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn common_ancestor_with() {
+    let tree = build();
+    let lca = |a: usize, b: usize| {
+        tree.node(a.into())
+            .unwrap()
+            .common_ancestor_with(b.into())
+            .map(|x| *x.data)
+    };
+    assert_eq!(lca(5, 6), Some(3));
+    assert_eq!(lca(9, 13), Some(7));
+    assert_eq!(lca(2, 14), Some(0));
+    assert_eq!(lca(5, 4), Some(4));
+    assert_eq!(lca(10, 10), Some(10));
+    assert_eq!(lca(10, 15), None);
+}
+
+#[test]
+fn compact() {
+    let mut tree = build();
+    // A level jump
+    let dead = tree.push_with_level(99, 5, 3.into());
+    // A child of a dead node
+    tree.push_with_level(100, 6, dead);
+    // A stale parent, not in pre-order
+    tree.push_with_level(101, 2, 1.into());
+    tree.push_with_level(15, 1, 0.into());
+    tree.push_with_level(16, 2, 18.into());
+    assert!(tree.validate().is_err());
+
+    let map = tree.compact();
+    assert!(tree.validate().is_ok());
+
+    let mut expected = build();
+    expected.tree_root_mut().push(15).push(16);
+    assert_eq!(tree, expected);
+
+    assert_eq!(map[14], Some(14.into()));
+    assert_eq!(map[15..18], [None, None, None]);
+    assert_eq!(map[18], Some(15.into()));
+    assert_eq!(map[19], Some(16.into()));
+}
+
+#[test]
+fn for_each_mut() {
+    let mut tree = build();
+    tree.for_each_mut(|level, parent, x| *x = (level * 100 + parent) as i32);
+
+    let expected: Vec<_> = build()
+        .iter()
+        .map(|x| (x.level() * 100 + x.parent()) as i32)
+        .collect();
+    assert_eq!(tree.as_data(), expected.as_slice());
+    assert_eq!(tree.as_data()[10], 308);
+}
+
+#[test]
+fn node_store() {
+    let tree = build();
+    let mut colors: NodeStore<&str> = tree.new_store();
+    assert_eq!(colors.len(), tree.len());
+    assert_eq!(colors.get(3.into()), Some(&""));
+
+    assert_eq!(colors.insert(3.into(), "red"), "");
+    *colors.get_mut(7.into()).unwrap() = "blue";
+    colors[14.into()] = "green";
+    assert!(colors.get(15.into()).is_none());
+
+    let found: Vec<_> = tree
+        .iter()
+        .filter(|x| !colors[x.id].is_empty())
+        .map(|x| (*x.data, colors[x.id]))
+        .collect();
+    assert_eq!(found, [(3, "red"), (7, "blue"), (14, "green")]);
+
+    colors.insert(16.into(), "grow");
+    assert_eq!(colors.len(), 17);
+}
+
+#[test]
+fn ancestors_with_level() {
+    let tree = build();
+    let ancestors: Vec<_> = tree
+        .node(10.into())
+        .unwrap()
+        .ancestors_with_level()
+        .map(|(x, level)| (*x.data, level))
+        .collect();
+    assert_eq!(ancestors, [(8, 2), (7, 1), (0, 0)]);
+    assert_eq!(tree.root().ancestors_with_level().count(), 0);
+
+    let node = tree.node(10.into()).unwrap();
+    assert_eq!(node.ancestors_with_level().len(), 3);
+    let levels: Vec<_> = node.ancestors_with_level().rev().map(|(_, l)| l).collect();
+    assert_eq!(levels, [0, 1, 2]);
+}
+
+#[test]
+fn first_last() {
+    let tree = build();
+    assert_eq!(tree.first().id, 0.into());
+    assert_eq!(tree.last().id, 14.into());
+    assert_eq!(tree.last().data, &14);
+    assert_eq!(tree.last_id(), 14.into());
+
+    let tree = Tree::new(0);
+    assert_eq!(tree.first(), tree.last());
+}
+
+#[test]
+fn can_push_child() {
+    let mut tree = Tree::new(0);
+    assert!(tree.can_push_child(0.into()));
+    let mut root = tree.tree_root_mut();
+    root.push(1).push(2);
+    assert!(tree.can_push_child(1.into()));
+    assert!(tree.can_push_child(2.into()));
+
+    tree.tree_root_mut().push(3);
+    assert!(tree.can_push_child(0.into()));
+    assert!(tree.can_push_child(3.into()));
+    assert!(!tree.can_push_child(1.into()));
+    assert!(!tree.can_push_child(2.into()));
+    assert!(!tree.can_push_child(4.into()));
+}
+
+#[test]
+fn paths() {
+    let tree = build();
+    let paths: Vec<_> = tree.paths().collect();
+    assert_eq!(paths.len(), tree.leaf_data().len());
+    assert_eq!(paths[0], [0.into(), 1.into(), 2.into()]);
+    assert_eq!(paths[4], [0.into(), 7.into(), 8.into(), 10.into()]);
+    assert_eq!(paths[7], [0.into(), 7.into(), 14.into()]);
+
+    assert_eq!(Tree::new(0).paths().collect::<Vec<_>>(), [[0.into()]]);
+}
+
+#[test]
+fn flatten_options() {
+    let tree = tree!(Some(0) => {
+        Some(1) => { Some(2) },
+        None => { Some(4) => { Some(5) }, Some(6) },
+        Some(7) => { None, Some(9) },
+    });
+    let flat = tree.flatten_options().unwrap();
+    assert_eq!(flat, tree!(0 => { 1 => { 2 }, 7 => { 9 } }));
+
+    assert!(Tree::<Option<i32>>::new(None).flatten_options().is_none());
+}
+
+#[test]
+fn push_child_at() {
+    let mut tree = Tree::new(0);
+    let child1 = tree.push_child_at(0.into(), 1).unwrap();
+    assert_eq!(tree.push_child_at(child1, 2), Ok(2.into()));
+    assert_eq!(tree.push_child_at(0.into(), 3), Ok(3.into()));
+
+    assert_eq!(tree.as_level(), [0, 1, 2, 1]);
+    assert_eq!(tree.as_parents(), [0, 0, 1, 0]);
+
+    assert_eq!(
+        tree.push_child_at(child1, 4),
+        Err(TreeError::NotAtTail(child1))
+    );
+    assert_eq!(
+        tree.push_child_at(10.into(), 4),
+        Err(TreeError::MissingParent)
+    );
+    assert_eq!(tree.len(), 4);
+}
+
+#[test]
+fn siblings_partition() {
+    let tree = build();
+    let ids = |nodes: Vec<Node<i32>>| nodes.iter().map(|x| *x.data).collect::<Vec<_>>();
+
+    let (before, after) = tree.node(11.into()).unwrap().siblings_partition();
+    assert_eq!(ids(before), [8]);
+    assert_eq!(ids(after), [14]);
+
+    let (before, after) = tree.node(1.into()).unwrap().siblings_partition();
+    assert!(before.is_empty());
+    assert_eq!(ids(after), [3, 7]);
+
+    let (before, after) = tree.node(5.into()).unwrap().siblings_partition();
+    assert!(before.is_empty() && after.is_empty());
+
+    let (before, after) = tree.root().siblings_partition();
+    assert!(before.is_empty() && after.is_empty());
+}
+
+#[test]
+fn with_children() {
+    let tree = Tree::with_children("root", ["a", "b", "c"]);
+    assert_eq!(tree.as_data(), ["root", "a", "b", "c"]);
+    assert_eq!(tree.as_level(), [0, 1, 1, 1]);
+    assert_eq!(tree.as_parents(), [0, 0, 0, 0]);
+    assert!(tree.capacity() >= 4);
+
+    assert_eq!(Tree::with_children(0, []), Tree::new(0));
+}
+
+#[test]
+fn parent_data() {
+    let tree = build();
+    assert_eq!(tree.node(5.into()).unwrap().parent_data(), Some(&4));
+    assert_eq!(tree.node(14.into()).unwrap().parent_data(), Some(&7));
+    assert_eq!(tree.node(1.into()).unwrap().parent_data(), Some(&0));
+    assert_eq!(tree.root().parent_data(), None);
+}
+
+#[test]
+fn iter_with_parents() {
+    let tree = build();
+    let pairs: Vec<_> = tree
+        .iter_with_parents()
+        .map(|(node, parent)| (*node.data, parent.map(|x| *x.data)))
+        .collect();
+    assert_eq!(pairs.len(), tree.len());
+    assert_eq!(pairs[0], (0, None));
+    assert_eq!(pairs[2], (2, Some(1)));
+    assert_eq!(pairs[10], (10, Some(8)));
+    assert_eq!(pairs[14], (14, Some(7)));
+    for (pos, (_, parent)) in pairs.iter().enumerate().skip(1) {
+        assert_eq!(parent.unwrap() as usize, tree.parent[pos]);
+    }
+}
+
+#[test]
+fn print() {
+    let tree = tree!("a" => {
+        "b" => { "c" => { "d" }, "e" },
+        "f" => { "g" => { "h", "i" } },
+    });
+    let expected = "\
+. a
+├── b
+│   ├── c
+│   │   └── d
+│   └── e
+└── f
+    └── g
+        ├── h
+        └── i
+";
+    assert_eq!(tree.to_string(), expected);
+
+    let expected = "\
+. 0
+├── 1
+│   └── 2
+├── 3
+│   ├── 4
+│   │   └── 5
+│   └── 6
+└── 7
+    ├── 8
+    │   ├── 9
+    │   └── 10
+    ├── 11
+    │   ├── 12
+    │   └── 13
+    └── 14
+";
+    assert_eq!(build().to_string(), expected);
+    assert_eq!(Tree::new(0).to_string(), ". 0\n");
+}
+
+#[test]
+fn print_glyph_widths() {
+    use crate::tree::{PRINT_BRANCH, PRINT_LAST, PRINT_PIPE, PRINT_SPACE};
+
+    let width = PRINT_PIPE.chars().count();
+    for glyph in [PRINT_BRANCH, PRINT_LAST, PRINT_SPACE] {
+        assert_eq!(glyph.chars().count(), width, "{:?}", glyph);
+    }
+}
+
+#[test]
+fn reversed() {
+    let tree = build();
+    let mirror = tree.reversed();
+    let expected = tree!(0 => {
+        7 => { 14, 11 => { 13, 12 }, 8 => { 10, 9 } },
+        3 => { 6, 4 => { 5 } },
+        1 => { 2 },
+    });
+    assert_eq!(mirror, expected);
+    assert_eq!(mirror.reversed(), tree);
+}
+
+#[test]
+fn count_matching() {
+    let tree = build();
+    assert_eq!(tree.count_matching(|x| x % 2 == 0), 8);
+    assert_eq!(tree.count_matching(|x| *x > 100), 0);
+}
+
+#[test]
+fn subtrees_mut() {
+    let mut tree = build();
+    {
+        let mut slices = tree.subtrees_mut(&[7.into(), 1.into(), 4.into()]).unwrap();
+        assert_eq!(slices[0], [7, 8, 9, 10, 11, 12, 13, 14]);
+        assert_eq!(slices[1], [1, 2]);
+        assert_eq!(slices[2], [4, 5]);
+        for slice in slices.iter_mut() {
+            slice.iter_mut().for_each(|x| *x *= 10);
+        }
+    }
+    assert_eq!(
+        tree.as_data(),
+        [0, 10, 20, 3, 40, 50, 6, 70, 80, 90, 100, 110, 120, 130, 140]
+    );
+
+    assert!(tree.subtrees_mut(&[7.into(), 8.into()]).is_none());
+    assert!(tree.subtrees_mut(&[3.into(), 3.into()]).is_none());
+    assert!(tree.subtrees_mut(&[0.into(), 14.into()]).is_none());
+    assert!(tree.subtrees_mut(&[15.into()]).is_none());
+    assert_eq!(tree.subtrees_mut(&[]).unwrap().len(), 0);
+}
+
+#[test]
+fn structure_hash() {
+    let tree = build();
+    let mut other = tree.clone();
+    other.for_each_mut(|_, _, x| *x *= 2);
+    assert_ne!(tree, other);
+    assert_eq!(tree.structure_hash(), other.structure_hash());
+
+    let other = tree.reversed();
+    assert_ne!(tree.structure_hash(), other.structure_hash());
+    assert_eq!(Tree::new(1).structure_hash(), Tree::new(2).structure_hash());
+}
+
+#[test]
+fn node_ids() {
+    let mut tree = build();
+    let mut store: NodeStore<usize> = tree.new_store();
+    for id in tree.node_ids() {
+        store[id] = tree.subtree_len(id);
+        *tree.node_mut(id).unwrap().data += 1;
+    }
+    assert_eq!(store.as_slice(), tree.subtree_sizes().as_slice());
+    assert_eq!(tree.root_data(), &1);
+
+    let ids: Vec<_> = tree.node_ids().collect();
+    assert_eq!(ids.len(), tree.len());
+    assert_eq!(ids[14], 14.into());
+}
+
+#[test]
+fn nth_at_level() {
+    let tree = build();
+    assert_eq!(tree.nth_at_level(1, 1).unwrap().id, 3.into());
+    assert_eq!(tree.nth_at_level(0, 0).unwrap().id, 0.into());
+    assert_eq!(tree.nth_at_level(3, 3).unwrap().id, 12.into());
+    assert!(tree.nth_at_level(1, 3).is_none());
+    assert!(tree.nth_at_level(4, 0).is_none());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_columnar() {
+    let tree = build();
+    let json = serde_json::to_string(&tree).unwrap();
+    assert!(json.starts_with(r#"{"data":[0,1,2,"#));
+    let back: Tree<i32> = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, tree);
+
+    let bad = r#"{"data":[0,1],"level":[0,2],"parent":[0,0]}"#;
+    assert!(serde_json::from_str::<Tree<i32>>(bad).is_err());
+}
+
+#[cfg(feature = "serde-nested")]
+#[test]
+fn serde_nested() {
+    use crate::nested::Nested;
+
+    let tree = build();
+    let json = serde_json::to_string(&Nested(&tree)).unwrap();
+    let Nested(back): Nested<Tree<i32>> = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, tree);
+
+    let json =
+        r#"{"value":"a","children":[{"value":"b","children":[{"value":"c"}]},{"value":"d"}]}"#;
+    let Nested(tree): Nested<Tree<String>> = serde_json::from_str(json).unwrap();
+    assert_eq!(tree.as_data(), ["a", "b", "c", "d"]);
+    assert_eq!(tree.as_level(), [0, 1, 2, 1]);
+    assert_eq!(tree.as_parents(), [0, 0, 1, 0]);
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Doc {
+        #[serde(with = "crate::nested")]
+        tree: Tree<String>,
+    }
+    let doc: Doc = serde_json::from_str(&format!(r#"{{"tree":{}}}"#, json)).unwrap();
+    assert_eq!(doc.tree, tree);
+    let again: Doc = serde_json::from_str(&serde_json::to_string(&doc).unwrap()).unwrap();
+    assert_eq!(again.tree, tree);
+}
+
+#[test]
+fn validate_report() {
+    assert_eq!(build().validate(), Ok(()));
+
+    let mut tree = build();
+    tree.level[6] = 5;
+    tree.parent[12] = 13;
+    assert_eq!(
+        tree.validate(),
+        Err(vec![
+            ValidationError::LevelJump {
+                node: 6,
+                expected: 4,
+                got: 5
+            },
+            ValidationError::ParentNotBefore {
+                node: 12,
+                parent: 13
+            },
+        ])
+    );
+
+    tree.level[0] = 1;
+    tree.parent[2] = 0;
+    let errors = tree.validate().unwrap_err();
+    assert_eq!(errors.len(), 4);
+    assert_eq!(
+        errors[0],
+        ValidationError::InvalidRoot {
+            level: 1,
+            parent: 0
+        }
+    );
+    assert_eq!(
+        errors[1],
+        ValidationError::ParentMismatch {
+            node: 2,
+            expected: 1,
+            got: 0
+        }
+    );
+    assert_eq!(
+        TreeError::from(errors[3]),
+        TreeError::InvalidParent(12.into())
+    );
+}
+
+#[test]
+fn as_columns() {
+    let tree = build();
+    let (data, level, parent) = tree.as_columns();
+    assert_eq!(data, tree.as_data());
+    assert_eq!(level, tree.as_level());
+    assert_eq!(parent, tree.as_parents());
+    assert_eq!(data.len(), level.len());
+    assert_eq!(parent[5], 4);
+}
+
+#[test]
+fn siblings_with_self() {
+    let tree = build();
+    let ids = |id: usize| {
+        tree.node(id.into())
+            .unwrap()
+            .siblings_with_self()
+            .map(|x| *x.data)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(ids(11), [8, 11, 14]);
+    assert_eq!(ids(8), [8, 11, 14]);
+    assert_eq!(ids(14), [8, 11, 14]);
+    assert_eq!(ids(2), [2]);
+    assert_eq!(ids(0), [0]);
+}
+
+#[test]
+fn max_min_by_key() {
+    let tree = build();
+    let max = tree.max_by_key(|x| *x).unwrap();
+    assert_eq!(max.id, 14.into());
+    assert_eq!(max.parent(), 7);
+    assert_eq!(tree.min_by_key(|x| *x).unwrap().id, 0.into());
+
+    // Ties: the last maximum & the first minimum
+    assert_eq!(tree.max_by_key(|x| x % 2).unwrap().id, 13.into());
+    assert_eq!(tree.min_by_key(|x| x % 2).unwrap().id, 0.into());
+}
+
+#[test]
+fn into_map() {
+    let tree = build();
+    let parents = tree.as_parents().to_vec();
+    let map = tree.into_map();
+    assert_eq!(map.len(), parents.len());
+    assert!(map.keys().copied().eq((0..parents.len()).map(NodeId::from)));
+    assert_eq!(map[&11.into()], 11);
+    assert_eq!(map[&parents[11].into()], 7);
+}
+
+#[test]
+fn drain_size_hint() {
+    let mut tree = build();
+    let mut drain = tree.drain(11..);
+    assert_eq!(drain.size_hint(), (4, Some(4)));
+    assert_eq!(drain.next(), Some((11, 2, 7.into())));
+    assert_eq!(drain.next_back(), Some((14, 2, 7.into())));
+    assert_eq!(drain.len(), 2);
+    assert_eq!(
+        drain.collect::<Vec<_>>(),
+        [(12, 3, 11.into()), (13, 3, 11.into())]
+    );
+    assert_eq!(tree.len(), 11);
+}
+
+#[test]
+fn try_get_level() {
+    let tree = build();
+    assert_eq!(tree.try_get_level(0.into()), Some(0));
+    assert_eq!(tree.try_get_level(12.into()), Some(3));
+    assert_eq!(tree.try_get_level(15.into()), None);
+    assert_eq!(tree.get_level(5.into()), 3);
+}
+
+#[test]
+#[should_panic(expected = "out of bounds")]
+fn get_level_out_of_bounds() {
+    build().get_level(15.into());
+}
+
+#[test]
+fn push_returning() {
+    let mut tree = Tree::new(String::from("Users"));
+    let mut root = tree.tree_root_mut();
+    let mut ids = Vec::new();
+    for name in ["jhon_doe", "jane_doe"] {
+        let (id, node) = root.push_returning(name.to_string());
+        node.data.push('/');
+        ids.push(id);
+    }
+    assert_eq!(ids, [1.into(), 2.into()]);
+    assert_eq!(tree.node(ids[1]).unwrap().data, "jane_doe/");
+    assert_eq!(tree.as_level(), [0, 1, 1]);
+}
+
+#[test]
+fn precedes() {
+    let tree = build();
+    let a = NodeId::from(4);
+    assert!(a.precedes(5.into()));
+    assert!(a.precedes(14.into()));
+    assert!(!a.precedes(a));
+    assert!(!a.precedes(3.into()));
+    // The parents come before in pre-order
+    let node = tree.node(13.into()).unwrap();
+    assert!(node.parents().all(|x| x.id.precedes(node.id)));
+}
+
+#[test]
+fn map_into() {
+    let tree = build();
+    let mapped = tree.map(|x| x.to_string());
+    assert!(mapped.same_shape(&tree));
+    assert_ne!(mapped.as_level().as_ptr(), tree.as_level().as_ptr());
+    assert_eq!(mapped.as_data()[11], "11");
+
+    let level = tree.as_level().as_ptr();
+    let parent = tree.as_parents().as_ptr();
+    let moved = tree.map_into(|x| x * 2);
+    // The levels & parents are reused, not copied
+    assert_eq!(moved.as_level().as_ptr(), level);
+    assert_eq!(moved.as_parents().as_ptr(), parent);
+    assert!(moved.same_shape(&mapped));
+    assert_eq!(moved.as_data()[11], 22);
+}
+
+#[test]
+fn boxed() {
+    let tree = build().map_into(|x| [x; 64]);
+    let boxed = tree.clone().boxed();
+    assert!(boxed.same_shape(&tree));
+    assert_eq!(boxed.node(5.into()).unwrap().data[63], 5);
+    assert_eq!(boxed.unboxed(), tree);
+}
+
+#[test]
+fn indent_cache() {
+    let tree = build();
+    assert_eq!(tree.indent_cache(0).max_level(), 3);
+    assert_eq!(tree.indent_cache(8).max_level(), 8);
+    assert_eq!(IndentCache::new(0).max_level(), 1);
+
+    let mut cached = Vec::new();
+    tree.write_tree_with(&mut cached, &tree.indent_cache(8))
+        .unwrap();
+    assert_eq!(String::from_utf8(cached).unwrap(), tree.to_string());
+
+    // Deeper levels than cached are still rendered
+    let mut cached = Vec::new();
+    tree.write_tree_with(&mut cached, &IndentCache::new(1))
+        .unwrap();
+    assert_eq!(String::from_utf8(cached).unwrap(), tree.to_string());
+}
+
+#[test]
+fn internal_nodes() {
+    let tree = build();
+    let ids = |it: &mut dyn Iterator<Item = Node<'_, i32>>| it.map(|x| *x.data).collect::<Vec<_>>();
+    assert_eq!(ids(&mut tree.internal_nodes()), [0, 1, 3, 4, 7, 8, 11]);
+    assert_eq!(ids(&mut tree.leaves()), [2, 5, 6, 9, 10, 12, 13, 14]);
+    assert_eq!(Tree::new(1).internal_nodes().count(), 0);
+}
+
+#[test]
+fn from_paths() {
+    let paths = ["a/b", "d", "a/c", "a", "d/e/f", "a/b"];
+    let tree = Tree::from_paths("/", paths.iter().map(|x| x.split('/')));
+    assert_eq!(tree.as_data(), ["/", "a", "b", "c", "d", "e", "f"]);
+    assert_eq!(tree.as_level(), [0, 1, 2, 2, 1, 2, 3]);
+    assert_eq!(tree.as_parents(), [0, 0, 1, 1, 0, 4, 5]);
+    assert_eq!(tree.validate(), Ok(()));
+
+    let empty: [Vec<&str>; 0] = [];
+    assert_eq!(Tree::from_paths("/", empty), Tree::new("/"));
+}
+
+#[test]
+fn from_levels() {
+    let tree = build();
+    let back = Tree::from_levels(tree.as_data().to_vec(), tree.as_level().to_vec());
+    assert_eq!(back, Ok(tree));
+
+    assert_eq!(
+        Tree::from_levels(vec![1, 2], vec![0, 2]),
+        Err(TreeError::InvalidLevel(1.into()))
+    );
+    assert_eq!(
+        Tree::from_levels(vec![1, 2], vec![0, 1, 1]),
+        Err(TreeError::LengthMismatch)
+    );
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn from_levels_par() {
+    // A deep & wide tree, with branches crossing the chunks
+    let mut level = vec![0];
+    for x in 1..200_000usize {
+        let prev = level[x - 1];
+        level.push(match x % 7 {
+            0 | 3 => prev + 1,
+            5 => prev.min(1 + x % 5).max(1),
+            _ if x % 1000 == 0 => 1,
+            _ => prev.max(1),
+        });
+    }
+    let data: Vec<_> = (0..level.len()).collect();
+
+    let seq = Tree::from_levels(data.clone(), level.clone()).unwrap();
+    let par = Tree::from_levels_par(data, level.clone()).unwrap();
+    assert_eq!(par, seq);
+    assert_eq!(par.validate(), Ok(()));
+
+    level[150_000] = level[149_999] + 2;
+    assert_eq!(
+        Tree::from_levels_par(vec![0; level.len()], level),
+        Err(TreeError::InvalidLevel(150_000.into()))
+    );
+}
+
+#[test]
+fn rightmost_leaf() {
+    let tree = build();
+    let leaf = |id: usize| tree.node(id.into()).unwrap().rightmost_leaf().id;
+    assert_eq!(leaf(7), 14.into());
+    assert_eq!(leaf(0), 14.into());
+    assert_eq!(leaf(3), 6.into());
+    assert_eq!(leaf(1), 2.into());
+    assert_eq!(leaf(5), 5.into());
+}
+
+#[test]
+fn leftmost_leaf() {
+    let tree = build();
+    let leaf = |id: usize| tree.node(id.into()).unwrap().leftmost_leaf().id;
+    assert_eq!(leaf(0), 2.into());
+    assert_eq!(leaf(3), 5.into());
+    assert_eq!(leaf(7), 9.into());
+    assert_eq!(leaf(11), 12.into());
+    assert_eq!(leaf(14), 14.into());
+}
+
+#[test]
+fn zip_mut() {
+    let mut names = build().map(|x| x.to_string());
+    let sizes = build().map_into(|x| x as usize);
+    names
+        .zip_mut(&sizes, |name, size| name.push_str(&format!(":{}", size)))
+        .unwrap();
+    assert_eq!(names.as_data()[11], "11:11");
+
+    let before = names.clone();
+    let other = tree!(1 => { 2 });
+    assert_eq!(
+        names.zip_mut(&other, |_, _| unreachable!()),
+        Err(TreeError::ShapeMismatch)
+    );
+    assert_eq!(names, before);
+}
+
+#[test]
+fn emit_events() {
+    let tree = build();
+    let events: Vec<_> = tree.emit_events().collect();
+    let opens = events
+        .iter()
+        .filter(|x| matches!(x, TreeEvent::Open(_)))
+        .count();
+    let closes = events.iter().filter(|x| **x == TreeEvent::Close).count();
+    assert_eq!(opens, 7);
+    assert_eq!(opens, closes);
+
+    // Never closes more than it opened
+    let mut depth = 0i32;
+    for x in &events {
+        match x {
+            TreeEvent::Open(_) => depth += 1,
+            TreeEvent::Close => depth -= 1,
+            TreeEvent::Leaf(_) => {}
+        }
+        assert!(depth >= 0);
+    }
+
+    let sexp: String = tree!(1 => { 2 => { 3 }, 4 })
+        .emit_events()
+        .map(|x| match x {
+            TreeEvent::Open(x) => format!("({}", x),
+            TreeEvent::Close => ")".into(),
+            TreeEvent::Leaf(x) => format!(" {}", x),
+        })
+        .collect();
+    assert_eq!(sexp, "(1(2 3) 4)");
+    assert_eq!(
+        Tree::new(1).emit_events().collect::<Vec<_>>(),
+        [TreeEvent::Leaf(&1)]
+    );
+}
+
+#[test]
+fn from_events() {
+    let tree = build();
+    let back = Tree::from_events(tree.emit_events().map(TreeEvent::cloned));
+    assert_eq!(back, Ok(tree));
+
+    use TreeEvent::*;
+    let tree = Tree::from_events(vec![Open("a"), Leaf("b"), Open("c"), Close, Close]).unwrap();
+    assert_eq!(tree.as_data(), ["a", "b", "c"]);
+    assert_eq!(tree.as_level(), [0, 1, 1]);
+
+    assert_eq!(
+        Tree::from_events(vec![Open(1), Close, Close]),
+        Err(TreeError::UnbalancedEvent(2))
+    );
+    assert_eq!(
+        Tree::from_events(vec![Leaf(1), Leaf(2)]),
+        Err(TreeError::UnbalancedEvent(1))
+    );
+    assert_eq!(
+        Tree::from_events(vec![Open(1), Leaf(2)]),
+        Err(TreeError::UnbalancedEvent(2))
+    );
+    assert_eq!(Tree::<i32>::from_events(vec![]), Err(TreeError::Empty));
+}
+
+#[test]
+fn memoize() {
+    let tree = build();
+    let counts = tree.memoize(|_, children: &[usize]| 1 + children.iter().sum::<usize>());
+    assert_eq!(counts, tree.subtree_sizes());
+
+    let heights = tree.memoize(|_, children: &[usize]| children.iter().max().map_or(0, |x| x + 1));
+    assert_eq!(heights[0], 3);
+    assert_eq!(heights[3], 2);
+    assert_eq!(heights[14], 0);
+
+    let sums = tree.memoize(|node, children: &[i32]| node.data + children.iter().sum::<i32>());
+    assert_eq!(sums[11], 11 + 12 + 13);
+}
+
+#[test]
+fn truncate_valid() {
+    let mut tree = build();
+    // In the middle of the subtree of 11
+    tree.truncate_valid(13);
+    assert_eq!(tree.validate(), Ok(()));
+    assert_eq!(tree.len(), 13);
+    assert_eq!(tree.node(11.into()).unwrap().children().count(), 1);
+
+    tree.truncate_valid(0);
+    assert_eq!(tree.validate(), Ok(()));
+    assert_eq!(tree, Tree::new(0));
+}
+
+#[test]
+fn children_contiguous_range() {
+    let tree = build();
+    let range = |id: usize| tree.node(id.into()).unwrap().children_contiguous_range();
+    assert_eq!(range(11), Some(12..14));
+    assert_eq!(range(8), Some(9..11));
+    assert_eq!(range(1), Some(2..3));
+    assert_eq!(range(7), None);
+    assert_eq!(range(3), None);
+    assert_eq!(range(14), Some(15..15));
+    assert_eq!(&tree.as_data()[range(11).unwrap()], [12, 13]);
+}
+
+#[test]
+fn reserve_for_depth() {
+    let mut tree = Tree::new(0);
+    tree.reserve_for_depth(3, 10);
+    let (data, level, parent) = tree.capacities();
+    assert!(data >= 1111 && level >= 1111 && parent >= 1111);
+
+    let mut tree = build();
+    tree.reserve_for_depth(0, 10);
+    assert!(tree.capacity() >= tree.len());
+    tree.reserve_for_depth(2, 4);
+    assert!(tree.capacity() >= 21);
+}
+
+#[test]
+fn parents_rev() {
+    let tree = build();
+    let node = tree.node(10.into()).unwrap();
+    let up: Vec<_> = node.parents().map(|x| *x.data).collect();
+    assert_eq!(up, [8, 7, 0]);
+    let levels: Vec<_> = node.parents().map(|x| x.level()).collect();
+    assert_eq!(levels, [2, 1, 0]);
+
+    let down: Vec<_> = node.parents().rev().map(|x| *x.data).collect();
+    assert_eq!(down, [0, 7, 8]);
+    assert_eq!(node.parents().len(), 3);
+
+    // Both ends meet in the middle
+    let mut it = tree.node(13.into()).unwrap().parents();
+    assert_eq!(it.next_back().map(|x| x.id), Some(0.into()));
+    assert_eq!(it.next().map(|x| x.id), Some(11.into()));
+    assert_eq!(it.next_back().map(|x| x.id), Some(7.into()));
+    assert!(it.next().is_none());
+    assert!(it.next_back().is_none());
+    assert_eq!(tree.root().parents().rev().count(), 0);
+}
+
+#[test]
+fn replace_subtree() {
+    let mut tree = build();
+    let new = tree!(70 => { 80 => { 90 }, 110 });
+    tree.replace_subtree(7.into(), new.clone()).unwrap();
+    assert_eq!(tree.as_data()[7..], [70, 80, 90, 110]);
+    assert_eq!(tree.as_level()[7..], [1, 2, 3, 2]);
+    assert_eq!(tree.as_parents()[7..], [0, 7, 8, 7]);
+    assert_eq!(tree.validate(), Ok(()));
+
+    let before = tree.clone();
+    assert_eq!(
+        tree.replace_subtree(3.into(), new.clone()),
+        Err(TreeError::NotAtTail(3.into()))
+    );
+    assert_eq!(tree, before);
+
+    let mut empty = Tree::new(99);
+    empty.clear();
+    assert_eq!(tree.replace_subtree(7.into(), empty), Err(TreeError::Empty));
+    assert_eq!(tree, before);
+
+    tree.replace_subtree(0.into(), new.clone()).unwrap();
+    assert_eq!(tree, new);
+}
+
+#[test]
+fn num_siblings() {
+    let tree = build();
+    let node = tree.node(8.into()).unwrap();
+    assert_eq!(node.num_siblings_same_parent(), 2);
+    assert_eq!(node.num_siblings_same_level(), 5);
+    assert_eq!(node.num_siblings_same_level(), node.siblings().count());
+    assert_eq!(tree.node(2.into()).unwrap().num_siblings_same_parent(), 0);
+    assert_eq!(tree.root().num_siblings_same_parent(), 0);
+    assert_eq!(tree.root().num_siblings_same_level(), 0);
+}
+
+#[test]
+fn root_parent() {
+    let tree = build();
+    assert_eq!(NodeId::ROOT, NodeId::from(0));
+    assert!(NodeId::ROOT.is_root());
+    assert!(!NodeId::from(1).is_root());
+
+    let root = tree.root();
+    assert_eq!(root.id, NodeId::ROOT);
+    // Stored as its own parent, but reported as without one
+    assert_eq!(root.parent(), 0);
+    assert_eq!(root.parent_id(), None);
+    assert_eq!(root.parent_data(), None);
+    assert_eq!(root.parents().count(), 0);
+    assert_eq!(tree.as_view().root().parent_id(), None);
+
+    assert_eq!(tree.node(1.into()).unwrap().parent_id(), Some(NodeId::ROOT));
+    assert_eq!(tree.node(12.into()).unwrap().parent_id(), Some(11.into()));
+}
+
+#[test]
+fn find_all() {
+    let tree = build();
+    let even: Vec<_> = tree.find_all(|x| x % 2 == 0).map(|x| *x.data).collect();
+    assert_eq!(even, [0, 2, 4, 6, 8, 10, 12, 14]);
+    assert_eq!(tree.find_all(|x| *x > 10).next().unwrap().id, 11.into());
+    assert_eq!(tree.find_all(|x| *x > 100).count(), 0);
+}
+
+#[test]
+fn subtree_eq() {
+    let tree = build();
+    let other = tree!(8 => { 9, 10 });
+    assert!(tree.subtree_eq(8.into(), &other, 0.into()));
+    assert!(other.subtree_eq(0.into(), &tree, 8.into()));
+    assert!(tree.subtree_eq(7.into(), &tree.clone(), 7.into()));
+    assert!(tree.subtree_eq(0.into(), &tree, 0.into()));
+
+    // Different data, size & shape
+    assert!(!tree.subtree_eq(11.into(), &other, 0.into()));
+    assert!(!tree.subtree_eq(7.into(), &other, 0.into()));
+    let flat = tree!(8 => { 9 => { 10 } });
+    assert!(!tree.subtree_eq(8.into(), &flat, 0.into()));
+    assert!(!tree.subtree_eq(15.into(), &other, 0.into()));
+}
+
+#[test]
+fn to_display_rows() {
+    let tree = build();
+    let rows = tree.to_display_rows();
+    assert_eq!(rows.len(), tree.len());
+    assert_eq!(rows[..4], [(0, &0), (1, &1), (2, &2), (1, &3)]);
+    assert_eq!(rows[14], (2, &14));
+}
+
+#[test]
+fn visible_rows() {
+    let tree = build();
+    let mut collapsed: NodeStore<bool> = tree.new_store();
+    let rows = |collapsed: &NodeStore<bool>| {
+        tree.visible_rows(collapsed)
+            .into_iter()
+            .map(|(level, x)| (level, *x.data))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(rows(&collapsed).len(), tree.len());
+
+    collapsed[3.into()] = true;
+    collapsed[8.into()] = true;
+    collapsed[12.into()] = true;
+    // Collapsed inside a collapsed node
+    collapsed[4.into()] = true;
+    assert_eq!(
+        rows(&collapsed),
+        [
+            (0, 0),
+            (1, 1),
+            (2, 2),
+            (1, 3),
+            (1, 7),
+            (2, 8),
+            (2, 11),
+            (3, 12),
+            (3, 13),
+            (2, 14)
+        ]
+    );
+
+    collapsed[0.into()] = true;
+    assert_eq!(rows(&collapsed), [(0, 0)]);
+    assert_eq!(rows(&NodeStore::default()).len(), tree.len());
+}
+
+#[test]
+fn scan() {
+    let tree = build();
+    let paths = tree.scan(String::new(), |parent, node| {
+        format!("{}/{}", parent, node.data)
+    });
+    assert_eq!(paths[0], "/0");
+    assert_eq!(paths[5], "/0/3/4/5");
+    assert_eq!(paths[13], "/0/7/11/13");
+
+    let depths = tree.scan(0, |parent, _| parent + 1);
+    let levels: Vec<_> = tree.as_level().iter().map(|x| x + 1).collect();
+    assert_eq!(depths, levels);
+}
+
+#[test]
+fn swap_subtrees() {
+    let mut tree = build();
+    tree.swap_subtrees(1.into(), 7.into()).unwrap();
+    assert_eq!(
+        tree.as_data(),
+        [0, 7, 8, 9, 10, 11, 12, 13, 14, 3, 4, 5, 6, 1, 2]
+    );
+    assert_eq!(
+        tree.as_level(),
+        [0, 1, 2, 3, 3, 2, 3, 3, 2, 1, 2, 3, 2, 1, 2]
+    );
+    assert_eq!(
+        tree.as_parents(),
+        [0, 0, 1, 2, 2, 1, 5, 5, 1, 0, 9, 10, 9, 0, 13]
+    );
+    assert_eq!(tree.validate(), Ok(()));
+
+    // Back, in any order of the arguments
+    tree.swap_subtrees(13.into(), 1.into()).unwrap();
+    assert_eq!(tree, build());
+
+    // At different levels
+    tree.swap_subtrees(5.into(), 11.into()).unwrap();
+    assert_eq!(tree.validate(), Ok(()));
+    assert_eq!(tree.node(5.into()).unwrap().data, &11);
+    assert_eq!(tree.node(5.into()).unwrap().level(), 3);
+    assert_eq!(tree.node(7.into()).unwrap().level(), 4);
+    assert_eq!(tree.node(13.into()).unwrap().data, &5);
+    assert_eq!(tree.node(13.into()).unwrap().parent(), 9);
+
+    let before = tree.clone();
+    assert_eq!(
+        tree.swap_subtrees(7.into(), 3.into()),
+        Err(TreeError::Overlapping(3.into(), 7.into()))
+    );
+    assert_eq!(tree.swap_subtrees(4.into(), 4.into()), Ok(()));
+    assert_eq!(tree, before);
+}
+
+#[test]
+fn level_index() {
+    let tree = build();
+    let index = tree.level_index();
+    assert_eq!(index.len(), 4);
+    let ids = |level: usize| {
+        index
+            .get(level)
+            .iter()
+            .map(|x| x.to_index())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(ids(0), [0]);
+    assert_eq!(ids(1), [1, 3, 7]);
+    assert_eq!(ids(2), [2, 4, 6, 8, 11, 14]);
+    assert_eq!(ids(3), [5, 9, 10, 12, 13]);
+    assert!(ids(4).is_empty());
+    assert_eq!(index.as_slice().len(), tree.len());
+    assert_eq!(Tree::new(1).level_index().get(0), [NodeId::ROOT]);
+}
+
+#[test]
+fn data_chunks() {
+    let tree = build();
+    let sums: Vec<i32> = tree.data_chunks(4).map(|x| x.iter().sum()).collect();
+    assert_eq!(sums, [6, 22, 38, 39]);
+    assert_eq!(sums.iter().sum::<i32>(), tree.as_data().iter().sum::<i32>());
+    assert_eq!(tree.data_chunks(100).count(), 1);
+}
+
+#[test]
+fn is_path() {
+    assert!(!build().is_path());
+    assert!(Tree::new(1).is_path());
+    assert!(tree!(1 => { 2 => { 3 => { 4 } } }).is_path());
+    assert!(!tree!(1 => { 2 => { 3 }, 4 }).is_path());
+    assert!(Tree::from_paths(0, [vec![1, 2, 3]]).is_path());
+}
+
+#[test]
+fn branching_stats() {
+    let stats = build().branching_stats();
+    assert_eq!(stats.internal, 7);
+    assert_eq!(stats.min, 1);
+    assert_eq!(stats.max, 3);
+    assert_eq!(stats.mean, 2.0);
+
+    assert_eq!(Tree::new(1).branching_stats(), Default::default());
+    let stats = Tree::with_children(0, 1..=4).branching_stats();
+    assert_eq!((stats.internal, stats.min, stats.max), (1, 4, 4));
+}
+
+#[test]
+fn set() {
+    let mut tree = build();
+    assert_eq!(tree.set(11.into(), 110), Some(11));
+    assert_eq!(tree.node(11.into()).unwrap().data, &110);
+    assert_eq!(tree.set(11.into(), 111), Some(110));
+    assert_eq!(tree.set(15.into(), 150), None);
+    assert_eq!(tree.len(), 15);
+}
+
+#[test]
+fn subtrees_of_level() {
+    let tree = build();
+    let roots: Vec<_> = tree.subtrees_of_level(1).map(|x| *x.data).collect();
+    assert_eq!(roots, [1, 3, 7]);
+    let roots: Vec<_> = tree.subtrees_of_level(3).map(|x| *x.data).collect();
+    assert_eq!(roots, [5, 9, 10, 12, 13]);
+    assert_eq!(tree.subtrees_of_level(0).count(), 1);
+    assert_eq!(tree.subtrees_of_level(4).count(), 0);
+
+    let sizes: Vec<_> = tree
+        .subtrees_of_level(1)
+        .map(|x| x.subtree().count())
+        .collect();
+    assert_eq!(sizes, [2, 4, 8]);
+    let branch: Vec<_> = tree
+        .node(8.into())
+        .unwrap()
+        .subtree()
+        .map(|x| *x.data)
+        .collect();
+    assert_eq!(branch, [8, 9, 10]);
+}
+
+#[test]
+fn intern() {
+    let tree = Tree::from_paths(
+        String::from("/"),
+        [["a", "lib.rs"], ["b", "lib.rs"], ["c", "main.rs"]]
+            .iter()
+            .map(|x| x.iter().map(|x| x.to_string())),
+    );
+    let interned = tree.clone().intern();
+    assert!(interned.same_shape(&tree));
+    let data = interned.as_data();
+    assert_eq!(&*data[2], "lib.rs");
+    assert!(std::sync::Arc::ptr_eq(&data[2], &data[4]));
+    assert!(!std::sync::Arc::ptr_eq(&data[2], &data[6]));
+
+    let data = tree.intern_rc().to_data();
+    assert!(std::rc::Rc::ptr_eq(&data[2], &data[4]));
+    assert_eq!(std::rc::Rc::strong_count(&data[2]), 2);
+}
+
+#[test]
+fn prune_walk() {
+    let mut tree = build();
+    let mut seen = Vec::new();
+    tree.prune_walk(|x| {
+        seen.push(*x);
+        *x *= 10;
+        if *x == 30 || *x == 110 {
+            Prune::PruneSubtree
+        } else {
+            Prune::Keep
+        }
+    });
+    // The pruned children are not visited
+    assert_eq!(seen, [0, 1, 2, 3, 7, 8, 9, 10, 11, 14]);
+    assert_eq!(tree.as_data(), [0, 10, 20, 70, 80, 90, 100, 140]);
+    assert_eq!(tree.as_level(), [0, 1, 2, 1, 2, 3, 3, 2]);
+    assert_eq!(tree.as_parents(), [0, 0, 1, 0, 3, 4, 4, 3]);
+    assert_eq!(tree.validate(), Ok(()));
+
+    tree.prune_walk(|_| Prune::PruneSubtree);
+    assert_eq!(tree, Tree::new(0));
+}
+
+#[test]
+fn level_pairs() {
+    let tree = build();
+    let pairs = tree.clone().into_level_pairs();
+    assert_eq!(pairs[..3], [(0, 0), (1, 1), (2, 2)]);
+    assert_eq!(Tree::from_level_pairs(pairs), Ok(tree));
+
+    let pairs = vec![("a", 0), ("b", 1), ("c", 3)];
+    assert_eq!(
+        Tree::from_level_pairs(pairs),
+        Err(TreeError::InvalidLevel(2.into()))
+    );
+    assert_eq!(Tree::<i32>::from_level_pairs(vec![]), Err(TreeError::Empty));
+}
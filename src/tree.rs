@@ -1,9 +1,14 @@
 #![allow(dead_code)]
 
-use crate::iter::{IntoIter, TreeIter};
+use crate::iter::{Drain, IntoIterRef, TreeIter};
 use crate::node::NodeMut;
 use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::convert::TryFrom;
 use std::fmt::{Debug, Display, Formatter};
+use std::hash::Hash;
+use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::prelude::*;
 
@@ -17,6 +22,251 @@ pub struct Tree<T> {
     pub(crate) parent: Vec<usize>,
 }
 
+// The glyphs of the pretty-printed tree. Each column is `PRINT_PIPE` or
+// `PRINT_SPACE`, followed by the branch, so all must have the same width (checked
+// in the tests)
+pub(crate) const PRINT_ROOT: &str = ". ";
+pub(crate) const PRINT_BRANCH: &str = "├── ";
+pub(crate) const PRINT_LAST: &str = "└── ";
+pub(crate) const PRINT_PIPE: &str = "│   ";
+pub(crate) const PRINT_SPACE: &str = "    ";
+
+/// The index *after* the last node of the subtree rooted at `idx`, from the levels.
+pub(crate) fn subtree_end(level: &[usize], idx: usize) -> usize {
+    let of = level[idx];
+    level[idx + 1..]
+        .iter()
+        .position(|l| *l <= of)
+        .map_or(level.len(), |pos| idx + 1 + pos)
+}
+
+/// Check the levels & parents of `len` nodes are consistent with a tree built in pre-order,
+/// collecting all the [ValidationError] found.
+pub(crate) fn validate_columns(
+    len: usize,
+    level: &[usize],
+    parent: &[usize],
+) -> Vec<ValidationError> {
+    if len == 0 {
+        return vec![ValidationError::Empty];
+    }
+    if level.len() != len || parent.len() != len {
+        return vec![ValidationError::LengthMismatch {
+            data: len,
+            level: level.len(),
+            parent: parent.len(),
+        }];
+    }
+    let mut errors = Vec::new();
+    if level[0] != 0 || parent[0] != 0 {
+        errors.push(ValidationError::InvalidRoot {
+            level: level[0],
+            parent: parent[0],
+        });
+    }
+    // The last node seen at each level, so the parent must be the last one of the level above
+    let mut last = vec![0];
+    for pos in 1..len {
+        let got = level[pos];
+        // On a bad level, keep checking as if the node was a child of his parent,
+        // so a single corrupt value is reported once
+        let fallback = || {
+            last.iter()
+                .position(|idx| *idx == parent[pos])
+                .map_or(last.len(), |of| of + 1)
+        };
+        let of = if got == 0 {
+            errors.push(ValidationError::ExtraRoot { node: pos });
+            fallback()
+        } else if got > last.len() {
+            errors.push(ValidationError::LevelJump {
+                node: pos,
+                expected: last.len(),
+                got,
+            });
+            fallback()
+        } else {
+            got
+        };
+        if parent[pos] >= pos {
+            errors.push(ValidationError::ParentNotBefore {
+                node: pos,
+                parent: parent[pos],
+            });
+        } else if parent[pos] != last[of - 1] {
+            errors.push(ValidationError::ParentMismatch {
+                node: pos,
+                expected: last[of - 1],
+                got: parent[pos],
+            });
+        }
+        last.truncate(of);
+        last.push(pos);
+    }
+    errors
+}
+
+/// Check the `level` of `len` nodes could be from a tree built in pre-order, for
+/// [Tree::from_levels].
+pub(crate) fn check_levels(len: usize, level: &[usize]) -> Result<(), TreeError> {
+    if len == 0 {
+        return Err(TreeError::Empty);
+    }
+    if level.len() != len {
+        return Err(TreeError::LengthMismatch);
+    }
+    if level[0] != 0 {
+        return Err(TreeError::InvalidLevel(0.into()));
+    }
+    match level_jump(level, 1..len) {
+        Some(pos) => Err(TreeError::InvalidLevel(pos.into())),
+        None => Ok(()),
+    }
+}
+
+/// The first position in `range` where the level is 0 or deeper than the child of
+/// the previous node.
+pub(crate) fn level_jump(level: &[usize], mut range: std::ops::Range<usize>) -> Option<usize> {
+    range.find(|pos| level[*pos] == 0 || level[*pos] > level[pos - 1] + 1)
+}
+
+/// A positional difference between two [Tree], as reported by [Tree::diff].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TreeDiff {
+    /// The node at the same position has different data, level or parent.
+    Changed(NodeId),
+    /// The node only exists in the other tree.
+    Added(NodeId),
+    /// The node only exists in this tree.
+    Removed(NodeId),
+}
+
+/// A step of the streaming walk of a [Tree], as returned by [Tree::emit_events],
+/// like the tags of XML or the parenthesis of S-expressions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TreeEvent<T> {
+    /// A node with children, that are next until the matching [TreeEvent::Close].
+    Open(T),
+    /// The end of the children of the last [TreeEvent::Open].
+    Close,
+    /// A node without children.
+    Leaf(T),
+}
+
+impl<T: Clone> TreeEvent<&T> {
+    /// Maps a `TreeEvent<&T>` to a `TreeEvent<T>` by cloning the data, like
+    /// [Option::cloned].
+    pub fn cloned(self) -> TreeEvent<T> {
+        match self {
+            TreeEvent::Open(x) => TreeEvent::Open(x.clone()),
+            TreeEvent::Close => TreeEvent::Close,
+            TreeEvent::Leaf(x) => TreeEvent::Leaf(x.clone()),
+        }
+    }
+}
+
+/// The [NodeId] of the nodes at each level, as returned by [Tree::level_index].
+///
+/// The levels are not contiguous in pre-order, so the ids are reordered by
+/// level (breadth-first) in a single vector, where each level is a slice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LevelIndex {
+    ids: Vec<NodeId>,
+    /// Where each level starts in `ids`, with the end of the last one at the end.
+    starts: Vec<usize>,
+}
+
+impl LevelIndex {
+    /// The number of levels.
+    pub fn len(&self) -> usize {
+        self.starts.len() - 1
+    }
+
+    /// Returns `true` if there are no levels.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The nodes at `level` in pre-order, or an empty slice if it is deeper than the tree.
+    pub fn get(&self, level: usize) -> &[NodeId] {
+        match (self.starts.get(level), self.starts.get(level + 1)) {
+            (Some(start), Some(end)) => &self.ids[*start..*end],
+            _ => &[],
+        }
+    }
+
+    /// All the nodes in breadth-first order, level by level.
+    pub fn as_slice(&self) -> &[NodeId] {
+        &self.ids
+    }
+}
+
+/// The number of *direct* children of the internal nodes, as returned by
+/// [Tree::branching_stats]. All are 0 for a tree with only the root.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct BranchingStats {
+    /// The number of internal nodes (with at least a child).
+    pub internal: usize,
+    /// The fewest children of an internal node.
+    pub min: usize,
+    /// The most children of a node.
+    pub max: usize,
+    /// The mean of children per internal node.
+    pub mean: f64,
+}
+
+/// What to do with a node visited by [Tree::prune_walk].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Prune {
+    /// Keep the node & continue with its children.
+    Keep,
+    /// Remove the node with all its subtree, without visiting the children.
+    PruneSubtree,
+}
+
+/// The columns of the pretty-printed tree precomputed up to a level, so
+/// [Tree::print_with] & [Tree::write_tree_with] don't build a prefix per line.
+///
+/// Build it once with [Tree::indent_cache] to print many trees.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndentCache {
+    max_level: usize,
+    pipes: String,
+    spaces: String,
+}
+
+impl IndentCache {
+    /// Precompute the columns of the levels up to `max_level` (at least 1).
+    pub fn new(max_level: usize) -> Self {
+        let max_level = max_level.max(1);
+        IndentCache {
+            max_level,
+            pipes: PRINT_PIPE.repeat(max_level),
+            spaces: PRINT_SPACE.repeat(max_level),
+        }
+    }
+
+    /// The deepest level cached.
+    pub fn max_level(&self) -> usize {
+        self.max_level
+    }
+
+    /// Push the slices of `n` columns of pipes (if `open`) or spaces, repeating
+    /// the cached ones if it is deeper than [Self::max_level].
+    fn push_columns<'a>(&'a self, pieces: &mut Vec<&'a str>, open: bool, mut n: usize) {
+        let (line, glyph) = if open {
+            (&self.pipes, PRINT_PIPE)
+        } else {
+            (&self.spaces, PRINT_SPACE)
+        };
+        while n > 0 {
+            let take = n.min(self.max_level);
+            pieces.push(&line[..take * glyph.len()]);
+            n -= take;
+        }
+    }
+}
+
 impl<T: Debug> Tree<T> {
     /// Create a new [Tree] with the specified value
     pub fn new(root: T) -> Self {
@@ -30,15 +280,217 @@ impl<T: Debug> Tree<T> {
             level: Vec::with_capacity(capacity),
             parent: Vec::with_capacity(capacity),
         };
-        t.push_with_level(root, 0, 0.into());
+        t.push_with_level(root, 0, NodeId::ROOT);
+        t
+    }
+
+    /// Create a new [Tree] with the `root` and a flat list of direct `children`
+    pub fn with_children(root: T, children: impl IntoIterator<Item = T>) -> Self {
+        let children = children.into_iter();
+        let mut t = Self::with_capacity(root, 1 + children.size_hint().0);
+        for child in children {
+            t.push_with_level(child, 1, NodeId::ROOT);
+        }
         t
     }
 
+    /// Create a new [Tree] from a map of `key -> (data, parent key)`, like the
+    /// ones loaded from relational sources, starting at `root`.
+    ///
+    /// The parent of `root` is ignored. The order of the siblings follows the
+    /// iteration of the map, so is unspecified.
+    ///
+    /// # Errors
+    ///
+    /// Returns [TreeError] if `root` or a parent is not in the map, or if some
+    /// nodes are in a cycle.
+    pub fn from_parent_map<K: Hash + Eq>(
+        root: K,
+        map: HashMap<K, (T, K)>,
+    ) -> Result<Tree<T>, TreeError> {
+        let mut index = HashMap::with_capacity(map.len());
+        let mut nodes = Vec::with_capacity(map.len());
+        let mut parents = Vec::with_capacity(map.len());
+        for (pos, (key, (data, parent))) in map.into_iter().enumerate() {
+            index.insert(key, pos);
+            nodes.push(Some(data));
+            parents.push(parent);
+        }
+
+        let root = *index.get(&root).ok_or(TreeError::MissingRoot)?;
+        let mut children = vec![Vec::new(); nodes.len()];
+        for (pos, parent) in parents.iter().enumerate() {
+            if pos != root {
+                let parent = *index.get(parent).ok_or(TreeError::MissingParent)?;
+                children[parent].push(pos);
+            }
+        }
+
+        let mut tree = Tree::with_capacity(nodes[root].take().unwrap(), nodes.len());
+        let mut stack: Vec<_> = children[root]
+            .iter()
+            .rev()
+            .map(|pos| (*pos, 1, NodeId::ROOT))
+            .collect();
+        while let Some((pos, level, parent)) = stack.pop() {
+            let id = tree.push_with_level(nodes[pos].take().unwrap(), level, parent);
+            stack.extend(children[pos].iter().rev().map(|x| (*x, level + 1, id)));
+        }
+
+        if tree.len() < nodes.len() {
+            Err(TreeError::Cycle)
+        } else {
+            Ok(tree)
+        }
+    }
+
+    /// Create a new [Tree] merging the shared prefixes of the `paths`, like a trie,
+    /// so each path is a chain of nodes from `root`.
+    ///
+    /// Unlike [Self::insert_path], the paths could come in any order. The children
+    /// follow the order they were first seen.
+    pub fn from_paths<P: IntoIterator<Item = T>>(
+        root: T,
+        paths: impl IntoIterator<Item = P>,
+    ) -> Tree<T>
+    where
+        T: PartialEq,
+    {
+        // The trie as (data, children), with the root at 0
+        let mut nodes = vec![(Some(root), Vec::new())];
+        for path in paths {
+            let mut pos = 0;
+            for part in path {
+                let found = nodes[pos]
+                    .1
+                    .iter()
+                    .copied()
+                    .find(|x: &usize| nodes[*x].0.as_ref() == Some(&part));
+                pos = match found {
+                    Some(child) => child,
+                    None => {
+                        nodes.push((Some(part), Vec::new()));
+                        let child = nodes.len() - 1;
+                        nodes[pos].1.push(child);
+                        child
+                    }
+                };
+            }
+        }
+
+        let mut tree = Tree::with_capacity(nodes[0].0.take().unwrap(), nodes.len());
+        let mut stack: Vec<_> = nodes[0]
+            .1
+            .iter()
+            .rev()
+            .map(|pos| (*pos, 1, NodeId::ROOT))
+            .collect();
+        while let Some((pos, level, parent)) = stack.pop() {
+            let id = tree.push_with_level(nodes[pos].0.take().unwrap(), level, parent);
+            stack.extend(nodes[pos].1.iter().rev().map(|x| (*x, level + 1, id)));
+        }
+        tree
+    }
+
+    /// Create a new [Tree] from the `data` & `level` of the nodes in pre-order,
+    /// computing the parents.
+    ///
+    /// # Errors
+    ///
+    /// Returns [TreeError::Empty] or [TreeError::LengthMismatch] if the vectors don't
+    /// match, or [TreeError::InvalidLevel] if the first level is not 0, or a level is
+    /// 0 or more than one level below the previous one.
+    pub fn from_levels(data: Vec<T>, level: Vec<usize>) -> Result<Tree<T>, TreeError> {
+        check_levels(data.len(), &level)?;
+        // The last node seen at each level
+        let mut last: Vec<usize> = Vec::new();
+        let mut parent = Vec::with_capacity(level.len());
+        for (pos, of) in level.iter().enumerate() {
+            last.truncate(*of);
+            parent.push(last.last().copied().unwrap_or(0));
+            last.push(pos);
+        }
+        Ok(Tree {
+            data,
+            level,
+            parent,
+        })
+    }
+
+    /// Create a new [Tree] from the [TreeEvent] of a depth-first walk, like the
+    /// ones of [Self::emit_events], tracking the open nodes in a stack.
+    ///
+    /// # Errors
+    ///
+    /// Returns [TreeError::Empty] if there are no events, or
+    /// [TreeError::UnbalancedEvent] if an event closes a node not opened, there is
+    /// more than a root, or some nodes are not closed at the end.
+    pub fn from_events<I: IntoIterator<Item = TreeEvent<T>>>(
+        events: I,
+    ) -> Result<Tree<T>, TreeError> {
+        let mut tree: Option<Tree<T>> = None;
+        // The nodes opened & not closed
+        let mut open: Vec<NodeId> = Vec::new();
+        let mut count = 0;
+        for (pos, event) in events.into_iter().enumerate() {
+            count = pos + 1;
+            let (x, is_open) = match event {
+                TreeEvent::Open(x) => (x, true),
+                TreeEvent::Leaf(x) => (x, false),
+                TreeEvent::Close => {
+                    open.pop().ok_or(TreeError::UnbalancedEvent(pos))?;
+                    continue;
+                }
+            };
+            let id = match tree.as_mut() {
+                None => {
+                    tree = Some(Tree::new(x));
+                    NodeId::ROOT
+                }
+                Some(tree) => {
+                    let parent = *open.last().ok_or(TreeError::UnbalancedEvent(pos))?;
+                    tree.push_with_level(x, open.len(), parent)
+                }
+            };
+            if is_open {
+                open.push(id);
+            }
+        }
+        if !open.is_empty() {
+            return Err(TreeError::UnbalancedEvent(count));
+        }
+        tree.ok_or(TreeError::Empty)
+    }
+
+    /// Create a new [Tree] from the `(data, level)` pairs in pre-order, the
+    /// inverse of [Self::into_level_pairs].
+    ///
+    /// # Errors
+    ///
+    /// The same of [Self::from_levels].
+    pub fn from_level_pairs(
+        pairs: impl IntoIterator<Item = (T, usize)>,
+    ) -> Result<Tree<T>, TreeError> {
+        let (data, level) = pairs.into_iter().unzip();
+        Tree::from_levels(data, level)
+    }
+
     /// Returns the total number of elements the tree can hold without reallocating.
     pub fn capacity(&self) -> usize {
         self.data.capacity() // Any of the three underlying vectors is good enough.
     }
 
+    /// Returns the capacity of each of the internal vectors, as `(data, level, parent)`.
+    ///
+    /// Useful to debug the reallocations, because they could diverge.
+    pub fn capacities(&self) -> (usize, usize, usize) {
+        (
+            self.data.capacity(),
+            self.level.capacity(),
+            self.parent.capacity(),
+        )
+    }
+
     /// Reserves capacity for at least `additional` more elements to be inserted
     /// in the given `Tree<T>`. The collection may reserve more space to
     /// speculatively avoid frequent reallocations. After calling `reserve`,
@@ -54,6 +506,26 @@ impl<T: Debug> Tree<T> {
         self.parent.reserve(additional);
     }
 
+    /// Reserves capacity for a whole balanced tree of `depth` levels below the
+    /// root, where each node has `branching` children, in one call.
+    ///
+    /// After calling it, the capacity will be at least the nodes of such a tree,
+    /// `1 + branching + branching^2 + ... + branching^depth`. The nodes already in
+    /// the tree are counted as part of it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity exceeds `isize::MAX` bytes.
+    pub fn reserve_for_depth(&mut self, depth: u32, branching: usize) {
+        let mut total: usize = 1;
+        let mut width: usize = 1;
+        for _ in 0..depth {
+            width = width.saturating_mul(branching);
+            total = total.saturating_add(width);
+        }
+        self.reserve(total.saturating_sub(self.len()));
+    }
+
     /// Reserves the minimum capacity for at least `additional` more elements to
     /// be inserted in the given `Tree<T>`. Unlike [`reserve`], this will not
     /// deliberately over-allocate to speculatively avoid frequent allocations.
@@ -167,6 +639,16 @@ impl<T: Debug> Tree<T> {
         self.parent.truncate(len);
     }
 
+    /// Like [Self::truncate], but always keeping the root, so the tree is still valid.
+    ///
+    /// Because the tree is in pre-order, the parents are always before their
+    /// children, so a prefix never has a parent out of range: cutting in the
+    /// middle of a subtree just drops its last descendants. Only the root could
+    /// be lost, so `len` is rounded up to 1.
+    pub fn truncate_valid(&mut self, len: usize) {
+        self.truncate(len.max(1));
+    }
+
     /// Push a node into the tree
     ///
     /// #WARNING
@@ -183,7 +665,25 @@ impl<T: Debug> Tree<T> {
         (self.data.len() - 1).into()
     }
 
-    pub(crate) fn _make_node(&self, id: NodeId) -> Node<T> {
+    /// Push a node as the last child of `parent`, at the level below it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [TreeError::MissingParent] if `parent` is out of bounds, or
+    /// [TreeError::NotAtTail] if the child will not be in pre-order
+    /// (see [Self::can_push_child]).
+    pub fn push_child_at(&mut self, parent: NodeId, data: T) -> Result<NodeId, TreeError> {
+        if parent.to_index() >= self.len() {
+            return Err(TreeError::MissingParent);
+        }
+        if !self.can_push_child(parent) {
+            return Err(TreeError::NotAtTail(parent));
+        }
+        let level = self.level[parent.to_index()] + 1;
+        Ok(self.push_with_level(data, level, parent))
+    }
+
+    pub(crate) fn _make_node(&self, id: NodeId) -> Node<'_, T> {
         Node {
             id,
             data: &self.data[id.to_index()],
@@ -191,14 +691,14 @@ impl<T: Debug> Tree<T> {
         }
     }
 
-    pub(crate) fn _make_node_mut(&mut self, id: NodeId) -> NodeMut<T> {
+    pub(crate) fn _make_node_mut(&mut self, id: NodeId) -> NodeMut<'_, T> {
         NodeMut {
             id,
             data: &mut self.data[id.to_index()],
         }
     }
 
-    pub(crate) fn _make_tree_mut(&mut self, id: NodeId, parent: NodeId) -> TreeMut<T> {
+    pub(crate) fn _make_tree_mut(&mut self, id: NodeId, parent: NodeId) -> TreeMut<'_, T> {
         TreeMut {
             id,
             parent,
@@ -206,6 +706,72 @@ impl<T: Debug> Tree<T> {
         }
     }
 
+    /// The index *after* the last node of the subtree rooted at `idx`.
+    ///
+    /// Because the tree is in pre-order, the subtree is the contiguous span
+    /// `idx..end` of nodes with a level deeper than the one of `idx`.
+    pub(crate) fn _subtree_end(&self, idx: usize) -> usize {
+        subtree_end(&self.level, idx)
+    }
+
+    /// Check if `idx` has no children, ie: the next node is not deeper.
+    pub(crate) fn _is_leaf(&self, idx: usize) -> bool {
+        !matches!(self.level.get(idx + 1), Some(level) if *level > self.level[idx])
+    }
+
+    /// The indices of the *direct* children of `idx`, jumping over the
+    /// subtree of each one.
+    pub(crate) fn _direct_children(&self, idx: usize) -> impl Iterator<Item = usize> + '_ {
+        let end = self._subtree_end(idx);
+        let mut pos = idx + 1;
+        std::iter::from_fn(move || {
+            if pos < end {
+                let child = pos;
+                pos = self._subtree_end(child);
+                Some(child)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Keep only the nodes marked in `keep`, compacting the vectors and
+    /// remapping the parents.
+    ///
+    /// #WARNING
+    ///
+    /// This assumes whole subtrees are removed, so the parent of a kept node
+    /// is also kept.
+    pub(crate) fn _retain_mask(&mut self, keep: &[bool]) {
+        let mut remap = Vec::with_capacity(keep.len());
+        let mut next = 0;
+        for k in keep {
+            remap.push(next);
+            if *k {
+                next += 1;
+            }
+        }
+
+        let mut pos = 0;
+        self.data.retain(|_| {
+            pos += 1;
+            keep[pos - 1]
+        });
+        let mut pos = 0;
+        self.level.retain(|_| {
+            pos += 1;
+            keep[pos - 1]
+        });
+        let mut pos = 0;
+        self.parent.retain(|_| {
+            pos += 1;
+            keep[pos - 1]
+        });
+        for p in self.parent.iter_mut() {
+            *p = remap[*p];
+        }
+    }
+
     /// Removes the last element from a tree and returns it as a triple
     /// `(data: T, level: usize, parent: NodeId)`, or [`None`] if it
     /// is empty.
@@ -237,25 +803,15 @@ impl<T: Debug> Tree<T> {
     /// If the returned iterator goes out of scope without being dropped (due to
     /// [`mem::forget`], for example), the tree may have lost and leaked
     /// elements arbitrarily, including elements outside the range.
-    //
-    // # Implementation
-    //
-    // The return type may be specialized as in `std::vec::Drain`, implementing more traits.
-    pub fn drain<R>(&mut self, range: R) -> impl Iterator<Item = (T, usize, NodeId)> + '_
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_, T>
     where
         R: std::ops::RangeBounds<usize> + Clone,
     {
-        let mut data_drain = self.data.drain(range.clone());
-        let mut level_drain = self.level.drain(range.clone());
-        let mut parent_drain = self.parent.drain(range);
-        std::iter::from_fn(move || match data_drain.next() {
-            Some(data) => {
-                let level = level_drain.next().unwrap();
-                let parent = parent_drain.next().unwrap().into();
-                Some((data, level, parent))
-            }
-            None => None,
-        })
+        Drain {
+            data: self.data.drain(range.clone()),
+            level: self.level.drain(range.clone()),
+            parent: self.parent.drain(range),
+        }
     }
 
     /// Clears the tree, removing all values.
@@ -282,21 +838,21 @@ impl<T: Debug> Tree<T> {
     /// Get a mutable [TreeMut<T>] handle of the root, so you can push children
     ///
     /// This always success
-    pub fn tree_root_mut(&mut self) -> TreeMut<T> {
-        self._make_tree_mut(0.into(), 0.into())
+    pub fn tree_root_mut(&mut self) -> TreeMut<'_, T> {
+        self._make_tree_mut(NodeId::ROOT, NodeId::ROOT)
     }
 
     /// Get a mutable [TreeMut<T>] from his [NodeId], so you can push children
-    pub fn tree_node_mut(&mut self, id: NodeId) -> Option<TreeMut<T>> {
+    pub fn tree_node_mut(&mut self, id: NodeId) -> Option<TreeMut<'_, T>> {
         if id.to_index() < self.data.len() {
-            Some(self._make_tree_mut(id, 0.into()))
+            Some(self._make_tree_mut(id, id))
         } else {
             None
         }
     }
 
     /// Get the [Node<T>] from his [NodeId]
-    pub fn node(&self, id: NodeId) -> Option<Node<T>> {
+    pub fn node(&self, id: NodeId) -> Option<Node<'_, T>> {
         if id.to_index() < self.data.len() {
             Some(self._make_node(id))
         } else {
@@ -305,12 +861,27 @@ impl<T: Debug> Tree<T> {
     }
 
     /// Get the root [Node<T>]
-    pub fn root(&self) -> Node<T> {
-        self._make_node(0.into())
+    pub fn root(&self) -> Node<'_, T> {
+        self._make_node(NodeId::ROOT)
+    }
+
+    /// Get the first [`Node<T>`] in pre-order, that is always the root
+    pub fn first(&self) -> Node<'_, T> {
+        self.root()
+    }
+
+    /// Get the last [`Node<T>`] in pre-order, the "tail" that the pushes are relative to
+    pub fn last(&self) -> Node<'_, T> {
+        self._make_node(self.last_id())
+    }
+
+    /// Get the [NodeId] of the last node in pre-order
+    pub fn last_id(&self) -> NodeId {
+        (self.len() - 1).into()
     }
 
     /// Get a mutable [NodeMut<T>] from his [NodeId].
-    pub fn node_mut(&mut self, id: NodeId) -> Option<NodeMut<T>> {
+    pub fn node_mut(&mut self, id: NodeId) -> Option<NodeMut<'_, T>> {
         if id.to_index() < self.data.len() {
             Some(self._make_node_mut(id))
         } else {
@@ -318,18 +889,218 @@ impl<T: Debug> Tree<T> {
         }
     }
 
+    /// Replace the data of the [NodeId], returning the old one, or [None] if it
+    /// is out of bounds (and `value` is dropped).
+    pub fn set(&mut self, id: NodeId, value: T) -> Option<T> {
+        self.data
+            .get_mut(id.to_index())
+            .map(|x| std::mem::replace(x, value))
+    }
+
     /// Get a mutable [NodeMut<T>] handle of the root.
     ///
     /// This always success
     pub fn root_mut(&mut self) -> NodeMut<'_, T> {
-        self._make_node_mut(0.into())
+        self._make_node_mut(NodeId::ROOT)
+    }
+
+    /// Get the data of the root.
+    ///
+    /// This always success
+    pub fn root_data(&self) -> &T {
+        &self.data[0]
+    }
+
+    /// Get the mutable data of the root.
+    ///
+    /// This always success
+    pub fn root_data_mut(&mut self) -> &mut T {
+        &mut self.data[0]
     }
 
     pub fn iter(&self) -> TreeIter<'_, T> {
         TreeIter { pos: 0, tree: self }
     }
-    pub fn into_iter(&self) -> IntoIter<T> {
-        IntoIter { tree: self }
+
+    /// An [Iterator] of all the [NodeId] in pre-order.
+    ///
+    /// It don't borrow the tree, so it can be mutated (or a [NodeStore] keyed by
+    /// the ids) while iterating.
+    pub fn node_ids(&self) -> impl ExactSizeIterator<Item = NodeId> {
+        (0..self.len()).map(NodeId::from_index)
+    }
+
+    /// An [ExactSizeIterator] of all the nodes in pre-order, like [Self::iter].
+    ///
+    /// Named like the `nodes` of `ego_tree`, to ease the migration from it.
+    pub fn nodes(&self) -> TreeIter<'_, T> {
+        self.iter()
+    }
+
+    /// An [Iterator] of the nodes with a pre-order index in `range`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds, like slicing a [Vec].
+    pub fn nodes_in_range(
+        &self,
+        range: impl std::ops::RangeBounds<usize>,
+    ) -> impl Iterator<Item = Node<'_, T>> {
+        let start = match range.start_bound() {
+            std::ops::Bound::Included(x) => *x,
+            std::ops::Bound::Excluded(x) => *x + 1,
+            std::ops::Bound::Unbounded => 0,
+        };
+        let len = self.level[(range.start_bound().cloned(), range.end_bound().cloned())].len();
+        (start..start + len).map(move |idx| self._make_node(idx.into()))
+    }
+
+    /// An [Iterator] in pre-order of each node paired with its parent, or [None] for the root.
+    pub fn iter_with_parents(&self) -> impl Iterator<Item = (Node<'_, T>, Option<Node<'_, T>>)> {
+        self.iter().map(move |node| {
+            let parent = if node.id.to_index() > 0 {
+                Some(self._make_node(node.parent().into()))
+            } else {
+                None
+            };
+            (node, parent)
+        })
+    }
+
+    /// An [Iterator] of every path from the root to a leaf, in pre-order.
+    pub fn paths(&self) -> impl Iterator<Item = Vec<NodeId>> + '_ {
+        let mut path = Vec::new();
+        (0..self.len()).filter_map(move |pos| {
+            path.truncate(self.level[pos]);
+            path.push(NodeId::from_index(pos));
+            if self._is_leaf(pos) {
+                Some(path.clone())
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Get the `n`th (0-based) [`Node<T>`] at the `level`, in pre-order.
+    pub fn nth_at_level(&self, level: usize, n: usize) -> Option<Node<'_, T>> {
+        self.level
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| **x == level)
+            .nth(n)
+            .map(|(pos, _)| self._make_node(pos.into()))
+    }
+
+    /// An [Iterator] in pre-order of the nodes with a `level <= max_level`.
+    pub fn iter_max_depth(&self, max_level: usize) -> impl Iterator<Item = Node<'_, T>> {
+        self.iter().filter(move |x| x.level() <= max_level)
+    }
+
+    /// The `(level, node)` of the visible rows of a file-explorer like widget, in
+    /// pre-order, where the nodes marked in `collapsed` hide all their subtree.
+    ///
+    /// A collapsed node is still visible, and the nodes not in the store are
+    /// expanded. Each hidden subtree is skipped at once, by its span.
+    pub fn visible_rows(&self, collapsed: &NodeStore<bool>) -> Vec<(usize, Node<'_, T>)> {
+        let mut rows = Vec::new();
+        let mut pos = 0;
+        while pos < self.len() {
+            rows.push((self.level[pos], self._make_node(pos.into())));
+            pos = if collapsed.get(pos.into()).copied().unwrap_or(false) {
+                self._subtree_end(pos)
+            } else {
+                pos + 1
+            };
+        }
+        rows
+    }
+
+    /// A borrowing [IntoIterator] of the nodes.
+    ///
+    /// To move out the data, use the [IntoIterator] of the [Tree] itself.
+    pub fn into_iter_ref(&self) -> IntoIterRef<'_, T> {
+        IntoIterRef { tree: self }
+    }
+
+    /// An [Iterator] in pre-order of all the nodes with data that match `pred`.
+    ///
+    /// It is lazy, so `.next()` finds the first one.
+    pub fn find_all<'a>(
+        &'a self,
+        pred: impl Fn(&T) -> bool + 'a,
+    ) -> impl Iterator<Item = Node<'a, T>> {
+        self.data
+            .iter()
+            .enumerate()
+            .filter(move |(_, x)| pred(x))
+            .map(move |(idx, _)| self._make_node(idx.into()))
+    }
+
+    /// An [Iterator] of the data in chunks of `n` (the last one could be shorter),
+    /// for batch processing where the structure is not needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    pub fn data_chunks(&self, n: usize) -> impl Iterator<Item = &[T]> {
+        self.data.chunks(n)
+    }
+
+    /// Count the nodes with data that match `pred`.
+    pub fn count_matching(&self, pred: impl Fn(&T) -> bool) -> usize {
+        self.data.iter().filter(|x| pred(x)).count()
+    }
+
+    /// The [`Node<T>`] with the maximum `f(data)`, scanning the data in one pass.
+    ///
+    /// If several are equally maximum, the last one in pre-order is returned.
+    pub fn max_by_key<K: Ord>(&self, f: impl Fn(&T) -> K) -> Option<Node<'_, T>> {
+        let (idx, _) = self.data.iter().enumerate().max_by_key(|(_, x)| f(x))?;
+        Some(self._make_node(idx.into()))
+    }
+
+    /// The [`Node<T>`] with the minimum `f(data)`, scanning the data in one pass.
+    ///
+    /// If several are equally minimum, the first one in pre-order is returned.
+    pub fn min_by_key<K: Ord>(&self, f: impl Fn(&T) -> K) -> Option<Node<'_, T>> {
+        let (idx, _) = self.data.iter().enumerate().min_by_key(|(_, x)| f(x))?;
+        Some(self._make_node(idx.into()))
+    }
+
+    /// Call `f` with each data in pre-order, stopping at the first error.
+    ///
+    /// It scans the data directly, without building each [`Node<T>`].
+    pub fn try_for_each_data<E>(&self, f: impl FnMut(&T) -> Result<(), E>) -> Result<(), E> {
+        self.data.iter().try_for_each(f)
+    }
+
+    /// Call `f` with the level, the parent & the mutable data of each node, in pre-order.
+    pub fn for_each_mut(&mut self, mut f: impl FnMut(usize, usize, &mut T)) {
+        for ((data, level), parent) in self.data.iter_mut().zip(&self.level).zip(&self.parent) {
+            f(*level, *parent, data);
+        }
+    }
+
+    /// Call `f` with the mutable data of each node in pre-order, removing the
+    /// subtrees where it returns [Prune::PruneSubtree], in a single pass.
+    ///
+    /// The pruned children are not visited. The root is always kept, so pruning it
+    /// only removes its children.
+    pub fn prune_walk(&mut self, mut f: impl FnMut(&mut T) -> Prune) {
+        let mut keep = vec![true; self.len()];
+        let mut pos = 0;
+        while pos < self.len() {
+            match f(&mut self.data[pos]) {
+                Prune::Keep => pos += 1,
+                Prune::PruneSubtree => {
+                    let end = self._subtree_end(pos);
+                    let start = pos.max(1);
+                    keep[start..end].iter_mut().for_each(|k| *k = false);
+                    pos = end;
+                }
+            }
+        }
+        self._retain_mask(&keep);
     }
 
     /// A slice view of the internal data
@@ -341,18 +1112,59 @@ impl<T: Debug> Tree<T> {
         self.data.as_mut_slice()
     }
 
+    /// Mutable slices of the data of the subtrees of `roots`, in the same order.
+    ///
+    /// Returns [None] if any root is out of bounds, or if the subtrees overlap.
+    pub fn subtrees_mut(&mut self, roots: &[NodeId]) -> Option<Vec<&mut [T]>> {
+        let mut spans = Vec::with_capacity(roots.len());
+        for (pos, id) in roots.iter().enumerate() {
+            let idx = id.to_index();
+            if idx >= self.len() {
+                return None;
+            }
+            spans.push((idx, self._subtree_end(idx), pos));
+        }
+        spans.sort_unstable();
+        if spans.windows(2).any(|x| x[0].1 > x[1].0) {
+            return None;
+        }
+
+        let mut slices: Vec<Option<&mut [T]>> = roots.iter().map(|_| None).collect();
+        let mut rest = self.data.as_mut_slice();
+        let mut offset = 0;
+        for (start, end, pos) in spans {
+            let (_, tail) = std::mem::take(&mut rest).split_at_mut(start - offset);
+            let (slice, tail) = tail.split_at_mut(end - start);
+            slices[pos] = Some(slice);
+            rest = tail;
+            offset = end;
+        }
+        slices.into_iter().collect()
+    }
+
     /// A slice view of the internal level
     pub fn as_level(&self) -> &[usize] {
         &self.level
     }
 
     /// Get the level from a [NodeId]
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [NodeId] is out of bounds, see [Self::try_get_level].
     pub fn get_level(&self, of: NodeId) -> usize {
-        if of.to_index() == 0 {
-            0
-        } else {
-            self.level[of.to_index()]
-        }
+        self.try_get_level(of).unwrap_or_else(|| {
+            panic!(
+                "the node {:?} is out of bounds, the len is {}",
+                of,
+                self.len()
+            )
+        })
+    }
+
+    /// Get the level from a [NodeId], or `None` if it is out of bounds
+    pub fn try_get_level(&self, of: NodeId) -> Option<usize> {
+        self.level.get(of.to_index()).copied()
     }
 
     /// A slice view of the internal parents
@@ -360,40 +1172,962 @@ impl<T: Debug> Tree<T> {
         &self.parent
     }
 
+    /// A slice view of the internal data, level & parents, at once
+    pub fn as_columns(&self) -> (&[T], &[usize], &[usize]) {
+        (&self.data, &self.level, &self.parent)
+    }
+
     /// Consume tree and move-out the data
     pub fn to_data(self) -> Vec<T> {
         self.data
     }
 
-    /// Pretty-print the tree
-    pub fn print(&self, f: &mut Formatter<'_>) -> std::fmt::Result
-    where
-        T: Display,
+    /// Consume tree and move-out the data paired with its level, in pre-order.
+    ///
+    /// The parents can be computed again from the levels, so it is the minimal
+    /// lossless form, see [Self::from_level_pairs].
+    pub fn into_level_pairs(self) -> Vec<(T, usize)> {
+        self.data.into_iter().zip(self.level).collect()
+    }
+
+    /// Consume tree and move-out the data, keyed by his [NodeId].
+    ///
+    /// The structure is lost, so keep [Self::as_parents] around if needed.
+    pub fn into_map(self) -> BTreeMap<NodeId, T> {
+        self.data
+            .into_iter()
+            .enumerate()
+            .map(|(idx, data)| (idx.into(), data))
+            .collect()
+    }
+
+    /// The lowest common ancestor of `a` and `b`, where a node is an ancestor of itself.
+    ///
+    /// Returns [None] if any of them is out of bounds.
+    pub fn lca(&self, a: NodeId, b: NodeId) -> Option<Node<'_, T>> {
+        let (mut a, mut b) = (a.to_index(), b.to_index());
+        if a >= self.len() || b >= self.len() {
+            return None;
+        }
+        while self.level[a] > self.level[b] {
+            a = self.parent[a];
+        }
+        while self.level[b] > self.level[a] {
+            b = self.parent[b];
+        }
+        while a != b {
+            a = self.parent[a];
+            b = self.parent[b];
+        }
+        Some(self._make_node(a.into()))
+    }
+
+    /// The number of nodes in the subtree of `id`, including itself.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is out of bounds.
+    pub fn subtree_len(&self, id: NodeId) -> usize {
+        let idx = id.to_index();
+        self._subtree_end(idx) - idx
+    }
+
+    /// The [Self::subtree_len] of each node, indexed by [NodeId].
+    ///
+    /// Computed in a single backward pass, adding the size of each node to its parent.
+    pub fn subtree_sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![1; self.len()];
+        for pos in (1..self.len()).rev() {
+            sizes[self.parent[pos]] += sizes[pos];
+        }
+        sizes
+    }
+
+    /// A bottom-up fold, where the value of each node is `f` of the [`Node<T>`] & the
+    /// values of its *direct* children, indexed by [NodeId].
+    ///
+    /// Computed in a single backward pass, so the children are always done before
+    /// their parent.
+    pub fn memoize<V: Clone>(&self, f: impl Fn(Node<'_, T>, &[V]) -> V) -> Vec<V> {
+        let mut values: Vec<Option<V>> = vec![None; self.len()];
+        let mut children = Vec::new();
+        for pos in (0..self.len()).rev() {
+            children.clear();
+            children.extend(
+                self._direct_children(pos)
+                    .map(|child| values[child].clone().unwrap()),
+            );
+            values[pos] = Some(f(self._make_node(pos.into()), &children));
+        }
+        values.into_iter().map(Option::unwrap).collect()
+    }
+
+    /// A top-down scan, where the state of each node is `f` of the state of its
+    /// parent & the [`Node<T>`], indexed by [NodeId]. The root gets `init` as the
+    /// state of its parent.
+    ///
+    /// Computed in a single forward pass, so the parents are always done before
+    /// their children. It is the inverse of [Self::memoize].
+    pub fn scan<S>(&self, init: S, mut f: impl FnMut(&S, Node<'_, T>) -> S) -> Vec<S> {
+        let mut states: Vec<S> = Vec::with_capacity(self.len());
+        for pos in 0..self.len() {
+            let parent = if pos > 0 {
+                &states[self.parent[pos]]
+            } else {
+                &init
+            };
+            let state = f(parent, self._make_node(pos.into()));
+            states.push(state);
+        }
+        states
+    }
+
+    /// The min/max/mean number of *direct* children of the internal nodes, in a
+    /// single pass counting the children of each parent.
+    pub fn branching_stats(&self) -> BranchingStats {
+        let mut children = vec![0usize; self.len()];
+        for parent in self.parent.iter().skip(1) {
+            children[*parent] += 1;
+        }
+        let internal: Vec<_> = children.into_iter().filter(|x| *x > 0).collect();
+        if internal.is_empty() {
+            return BranchingStats::default();
+        }
+        BranchingStats {
+            internal: internal.len(),
+            min: internal.iter().copied().min().unwrap_or(0),
+            max: internal.iter().copied().max().unwrap_or(0),
+            mean: (self.len() - 1) as f64 / internal.len() as f64,
+        }
+    }
+
+    /// The data of all the leaves (nodes without children), in pre-order.
+    pub fn leaf_data(&self) -> Vec<&T> {
+        (0..self.len())
+            .filter(|idx| self._is_leaf(*idx))
+            .map(|idx| &self.data[idx])
+            .collect()
+    }
+
+    /// An [Iterator] in pre-order of the roots of the subtrees at `level`, so each
+    /// [Node::subtree] is an independent branch, like the top-level directories.
+    ///
+    /// It jumps over each subtree, so the nodes below `level` are not scanned.
+    pub fn subtrees_of_level(&self, level: usize) -> impl Iterator<Item = Node<'_, T>> {
+        let mut pos = 0;
+        std::iter::from_fn(move || {
+            while pos < self.len() {
+                let idx = pos;
+                if self.level[idx] == level {
+                    pos = self._subtree_end(idx);
+                    return Some(self._make_node(idx.into()));
+                }
+                pos += 1;
+            }
+            None
+        })
+    }
+
+    /// An [Iterator] in pre-order of the leaves (nodes without children).
+    pub fn leaves(&self) -> impl Iterator<Item = Node<'_, T>> {
+        (0..self.len())
+            .filter(move |idx| self._is_leaf(*idx))
+            .map(move |idx| self._make_node(idx.into()))
+    }
+
+    /// An [Iterator] in pre-order of the internal nodes (nodes with at least a
+    /// child), the inverse of [Self::leaves].
+    pub fn internal_nodes(&self) -> impl Iterator<Item = Node<'_, T>> {
+        (0..self.len())
+            .filter(move |idx| !self._is_leaf(*idx))
+            .map(move |idx| self._make_node(idx.into()))
+    }
+
+    /// Check the levels & parents are consistent with a tree built in pre-order.
+    ///
+    /// # Errors
+    ///
+    /// Returns all the [ValidationError] found, in the order of the nodes.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let errors = validate_columns(self.len(), &self.level, &self.parent);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Remove the nodes that are not reachable in pre-order from the root, like
+    /// the ones left with a wrong level or a stale parent after manual edits, and
+    /// remap the parents of the rest.
+    ///
+    /// Returns the new [NodeId] of each old one, or [None] if it was removed.
+    pub fn compact(&mut self) -> Vec<Option<NodeId>> {
+        let mut keep = vec![false; self.len()];
+        let mut map = vec![None; self.len()];
+        if self.is_empty() {
+            return map;
+        }
+        keep[0] = true;
+        map[0] = Some(NodeId::ROOT);
+        let mut next = 1;
+        // The last kept node at each level, so the parent must be the last one of the level above
+        let mut last = vec![0];
+        for pos in 1..self.len() {
+            let level = self.level[pos];
+            if level > 0 && level <= last.len() && self.parent[pos] == last[level - 1] {
+                keep[pos] = true;
+                map[pos] = Some(next.into());
+                next += 1;
+                last.truncate(level);
+                last.push(pos);
+            }
+        }
+        self._retain_mask(&keep);
+        map
+    }
+
+    /// Remove the direct children of `parent` with the same data of a previous
+    /// sibling, dropping the whole subtree of the duplicated child.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `parent` is out of bounds.
+    pub fn dedup_children(&mut self, parent: NodeId)
+    where
+        T: PartialEq,
     {
-        let last = self.data.len() - 1;
-        for (pos, x) in self.data.iter().enumerate() {
-            let mut branch = if pos == 0 {
-                "."
-            } else if pos == last {
-                "└──"
+        let mut keep = vec![true; self.len()];
+        let mut seen: Vec<usize> = Vec::new();
+        for child in self._direct_children(parent.to_index()) {
+            if seen.iter().any(|x| self.data[*x] == self.data[child]) {
+                let end = self._subtree_end(child);
+                keep[child..end].iter_mut().for_each(|k| *k = false);
+            } else {
+                seen.push(child);
+            }
+        }
+        self._retain_mask(&keep);
+    }
+
+    /// Remove the node `id`, moving its children to the parent of `id` and
+    /// folding the data of `id` into the parent with `merge`.
+    ///
+    /// The children stay in place, so the tree is still in pre-order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is the root or is out of bounds.
+    pub fn collapse(&mut self, id: NodeId, merge: impl FnOnce(&mut T, T)) {
+        let idx = id.to_index();
+        assert!(idx > 0, "the root can't be collapsed");
+        let parent = self.parent[idx];
+        let end = self._subtree_end(idx);
+        for level in &mut self.level[idx + 1..end] {
+            *level -= 1;
+        }
+
+        let data = self.data.remove(idx);
+        self.level.remove(idx);
+        self.parent.remove(idx);
+        for p in self.parent.iter_mut() {
+            match (*p).cmp(&idx) {
+                Ordering::Equal => *p = parent,
+                Ordering::Greater => *p -= 1,
+                Ordering::Less => {}
+            }
+        }
+
+        merge(&mut self.data[parent], data);
+    }
+
+    /// Move the subtree of `id` up one level, as the next sibling of its parent.
+    ///
+    /// This only keeps the pre-order when `id` is the *last* child of its parent,
+    /// so the subtree is already placed just after the parent's one. Returns
+    /// `false` without changes if this is not the case, or if `id` is the root or
+    /// a child of the root.
+    pub fn promote(&mut self, id: NodeId) -> bool {
+        let idx = id.to_index();
+        if idx == 0 || idx >= self.len() {
+            return false;
+        }
+        let parent = self.parent[idx];
+        if parent == 0 || self._subtree_end(idx) != self._subtree_end(parent) {
+            return false;
+        }
+
+        let end = self._subtree_end(idx);
+        for level in &mut self.level[idx..end] {
+            *level -= 1;
+        }
+        self.parent[idx] = self.parent[parent];
+        true
+    }
+
+    /// Check if a new child of `parent` will be in pre-order, ie: the subtree of
+    /// `parent` is at the end of the tree, so it don't need a mid-tree insertion.
+    pub fn can_push_child(&self, parent: NodeId) -> bool {
+        let idx = parent.to_index();
+        idx < self.len() && self._subtree_end(idx) == self.len()
+    }
+
+    /// Replace the subtree of `id` with the tree `new`, placed where `id` was,
+    /// under the same parent. Replacing the root replaces the whole tree.
+    ///
+    /// Only a subtree at the end of the tree can be replaced, because the new one
+    /// could have a different size and it will need a mid-tree insertion.
+    ///
+    /// # Errors
+    ///
+    /// Returns [TreeError::NotAtTail] if the subtree of `id` is not at the end of
+    /// the tree (or `id` is out of bounds), or [TreeError::Empty] if `new` has no
+    /// nodes. The tree is not changed in these cases.
+    pub fn replace_subtree(&mut self, id: NodeId, new: Tree<T>) -> Result<(), TreeError> {
+        if new.is_empty() {
+            return Err(TreeError::Empty);
+        }
+        if !self.can_push_child(id) {
+            return Err(TreeError::NotAtTail(id));
+        }
+        let idx = id.to_index();
+        let (level, parent) = (self.level[idx], self.parent[idx]);
+        self.truncate(idx);
+        self.reserve(new.len());
+        self.data.extend(new.data);
+        self.level.extend(new.level.iter().map(|x| x + level));
+        self.parent.extend(new.parent.iter().map(|x| x + idx));
+        self.parent[idx] = parent;
+        Ok(())
+    }
+
+    /// Swap the subtrees of `a` & `b`, so each one takes the place (parent &
+    /// level) of the other, and the nodes in between stay in the middle.
+    ///
+    /// Any two subtrees that don't contain each other keep the tree in pre-order,
+    /// because the levels of each one are rebased to the level of the other.
+    /// Swapping a node with itself does nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns [TreeError::Overlapping] if one subtree contains the other, without
+    /// changing the tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a` or `b` are out of bounds.
+    pub fn swap_subtrees(&mut self, a: NodeId, b: NodeId) -> Result<(), TreeError> {
+        let (a, b) = if a <= b { (a, b) } else { (b, a) };
+        let (a, b) = (a.to_index(), b.to_index());
+        let (end_a, end_b) = (self._subtree_end(a), self._subtree_end(b));
+        if a == b {
+            return Ok(());
+        }
+        if b < end_a {
+            return Err(TreeError::Overlapping(a.into(), b.into()));
+        }
+        let (len_a, len_mid) = (end_a - a, b - end_a);
+        let len_b = end_b - b;
+        let (level_a, level_b) = (self.level[a], self.level[b]);
+        let (parent_a, parent_b) = (self.parent[a], self.parent[b]);
+        // The new position of an old index
+        let remap = |x: usize| {
+            if x < a || x >= end_b {
+                x
+            } else if x < end_a {
+                x + len_b + len_mid
+            } else if x < b {
+                x + len_b - len_a
+            } else {
+                x - b + a
+            }
+        };
+
+        // Turn `A mid B` into `B mid A` reversing all, then each block
+        for (start, end) in [
+            (a, end_b),
+            (a, a + len_b),
+            (a + len_b, b + len_b - len_a),
+            (end_b - len_a, end_b),
+        ] {
+            self.data[start..end].reverse();
+            self.level[start..end].reverse();
+            self.parent[start..end].reverse();
+        }
+        for pos in a..self.len() {
+            self.parent[pos] = remap(self.parent[pos]);
+        }
+        for pos in a..a + len_b {
+            self.level[pos] = self.level[pos] - level_b + level_a;
+        }
+        for pos in end_b - len_a..end_b {
+            self.level[pos] = self.level[pos] - level_a + level_b;
+        }
+        self.parent[a] = parent_a;
+        self.parent[end_b - len_a] = remap(parent_b);
+        Ok(())
+    }
+
+    /// Walk from the root the chain of children matching each part of `path`,
+    /// pushing the ones not found, like a trie. Returns the [NodeId] of the last one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [TreeError::NotAtTail] if a part must be pushed to a node that is not
+    /// at the end of the tree, because it will need a mid-tree insertion. The tree
+    /// is not changed in this case.
+    pub fn insert_path<I: IntoIterator<Item = T>>(&mut self, path: I) -> Result<NodeId, TreeError>
+    where
+        T: PartialEq,
+    {
+        let mut id = 0;
+        for part in path {
+            let found = self._direct_children(id).find(|x| self.data[*x] == part);
+            match found {
+                Some(child) => id = child,
+                None => {
+                    if !self.can_push_child(id.into()) {
+                        return Err(TreeError::NotAtTail(id.into()));
+                    }
+                    id = self
+                        .push_with_level(part, self.level[id] + 1, id.into())
+                        .to_index();
+                }
+            }
+        }
+        Ok(id.into())
+    }
+
+    /// Overlay `other` into this tree, matching the children of matched nodes by
+    /// `key` and folding their data with `combine`. The roots are always matched.
+    ///
+    /// The children without a match in `other` are placed, with all their subtree,
+    /// after the children of the matched node. Because this could need a mid-tree
+    /// insertion, the vectors are rebuilt in a single pass instead of appending.
+    pub fn merge<K: PartialEq>(
+        &mut self,
+        other: &Tree<T>,
+        key: impl Fn(&T) -> K,
+        combine: impl Fn(&mut T, &T),
+    ) where
+        T: Clone,
+    {
+        enum Src {
+            Mine(usize, Option<usize>),
+            Other(usize),
+        }
+        // The nodes of the merged tree in pre-order, as (source, level, parent)
+        let mut order = Vec::with_capacity(self.len() + other.len());
+        let mut stack = vec![(Src::Mine(0, Some(0)), 0, 0)];
+        while let Some((src, level, parent)) = stack.pop() {
+            let pos = order.len();
+            let mut children = Vec::new();
+            match src {
+                Src::Mine(idx, theirs) => {
+                    let mut unmatched: Vec<_> = theirs
+                        .map(|x| other._direct_children(x).collect())
+                        .unwrap_or_default();
+                    for child in self._direct_children(idx) {
+                        let k = key(&self.data[child]);
+                        let found = unmatched.iter().position(|x| key(&other.data[*x]) == k);
+                        children.push(Src::Mine(child, found.map(|x| unmatched.remove(x))));
+                    }
+                    children.extend(unmatched.into_iter().map(Src::Other));
+                }
+                Src::Other(idx) => children.extend(other._direct_children(idx).map(Src::Other)),
+            }
+            stack.extend(children.into_iter().rev().map(|x| (x, level + 1, pos)));
+            order.push((src, level, parent));
+        }
+
+        let mut mine: Vec<_> = std::mem::take(&mut self.data)
+            .into_iter()
+            .map(Some)
+            .collect();
+        self.clear();
+        self.reserve(order.len());
+        for (src, level, parent) in order {
+            let data = match src {
+                Src::Mine(idx, theirs) => {
+                    let mut data = mine[idx].take().unwrap();
+                    if let Some(theirs) = theirs {
+                        combine(&mut data, &other.data[theirs]);
+                    }
+                    data
+                }
+                Src::Other(idx) => other.data[idx].clone(),
+            };
+            self.push_with_level(data, level, parent.into());
+        }
+    }
+
+    /// A mirror of the tree, with the order of the siblings recursively reversed.
+    pub fn reversed(&self) -> Tree<T>
+    where
+        T: Clone,
+    {
+        let mut tree = Tree::with_capacity(self.data[0].clone(), self.len());
+        // Pushing the children in order, they are popped in reverse
+        let mut stack: Vec<_> = self._direct_children(0).map(|x| (x, 0)).collect();
+        while let Some((idx, parent)) = stack.pop() {
+            let id = tree.push_with_level(self.data[idx].clone(), self.level[idx], parent.into());
+            stack.extend(self._direct_children(idx).map(|x| (x, id.to_index())));
+        }
+        tree
+    }
+
+    /// A new [Tree] with the same shape, transforming the data with `f`.
+    ///
+    /// It copies the levels & parents, see [Self::map_into] to reuse them.
+    pub fn map<U: Debug>(&self, f: impl FnMut(&T) -> U) -> Tree<U> {
+        Tree {
+            data: self.data.iter().map(f).collect(),
+            level: self.level.clone(),
+            parent: self.parent.clone(),
+        }
+    }
+
+    /// Consume tree and transform the data with `f`, keeping the shape.
+    ///
+    /// The levels & parents are moved, without copying them.
+    pub fn map_into<U: Debug>(self, f: impl FnMut(T) -> U) -> Tree<U> {
+        Tree {
+            data: self.data.into_iter().map(f).collect(),
+            level: self.level,
+            parent: self.parent,
+        }
+    }
+
+    /// Consume tree and move each data behind a [Box], keeping the shape.
+    ///
+    /// The levels & parents are already in their own vectors, so walking the
+    /// structure never touches the data. Boxing huge payloads also keeps the
+    /// data vector small, so growing & moving the tree is cheap.
+    pub fn boxed(self) -> Tree<Box<T>> {
+        self.map_into(Box::new)
+    }
+
+    /// Check if every node has at most one child, so the tree is a single chain.
+    ///
+    /// In pre-order it means each node is one level below the previous one.
+    pub fn is_path(&self) -> bool {
+        self.level
+            .iter()
+            .enumerate()
+            .all(|(pos, level)| pos == *level)
+    }
+
+    /// Check if both trees have the same levels & parents, ignoring the data.
+    pub fn same_shape<U>(&self, other: &Tree<U>) -> bool {
+        self.level == other.level && self.parent == other.parent
+    }
+
+    /// Check if the subtree of `a` in this tree is equal to the subtree of `b` in
+    /// `other`, comparing the data & the shape relative to each subtree root.
+    ///
+    /// Returns `false` if `a` or `b` are out of bounds.
+    pub fn subtree_eq(&self, a: NodeId, other: &Tree<T>, b: NodeId) -> bool
+    where
+        T: PartialEq,
+    {
+        let (a, b) = (a.to_index(), b.to_index());
+        if a >= self.len() || b >= other.len() {
+            return false;
+        }
+        let (end_a, end_b) = (self._subtree_end(a), other._subtree_end(b));
+        if end_a - a != end_b - b {
+            return false;
+        }
+        let (level_a, level_b) = (self.level[a], other.level[b]);
+        // The root of each subtree is compared by data only, its parent is outside
+        self.data[a] == other.data[b]
+            && (a + 1..end_a).zip(b + 1..end_b).all(|(x, y)| {
+                self.level[x] - level_a == other.level[y] - level_b
+                    && self.parent[x] - a == other.parent[y] - b
+                    && self.data[x] == other.data[y]
+            })
+    }
+
+    /// A hash of the levels & parents, ignoring the data, as a fast check before
+    /// [Self::same_shape].
+    ///
+    /// It uses FNV-1a, so the value is the same across runs & platforms.
+    pub fn structure_hash(&self) -> u64 {
+        const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let mut hash = OFFSET;
+        for x in self.level.iter().chain(self.parent.iter()) {
+            for byte in (*x as u64).to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(PRIME);
+            }
+        }
+        hash
+    }
+
+    /// Pair the data of two trees *with the same shape* in pre-order.
+    ///
+    /// Return [None] if the trees are not [Self::same_shape].
+    pub fn zip<'a, U>(
+        &'a self,
+        other: &'a Tree<U>,
+    ) -> Option<impl Iterator<Item = (&'a T, &'a U)>> {
+        if self.same_shape(other) {
+            Some(self.data.iter().zip(other.data.iter()))
+        } else {
+            None
+        }
+    }
+
+    /// Call `f` with the mutable data of this tree & the data of `other` at the
+    /// same position, in pre-order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [TreeError::ShapeMismatch] if the trees are not [Self::same_shape],
+    /// without calling `f`.
+    pub fn zip_mut<U>(
+        &mut self,
+        other: &Tree<U>,
+        mut f: impl FnMut(&mut T, &U),
+    ) -> Result<(), TreeError> {
+        if !self.same_shape(other) {
+            return Err(TreeError::ShapeMismatch);
+        }
+        for (x, y) in self.data.iter_mut().zip(other.data.iter()) {
+            f(x, y);
+        }
+        Ok(())
+    }
+
+    /// Compare the nodes of both trees at the same pre-order position.
+    ///
+    /// The extra nodes at the tail of the longer tree are reported as
+    /// [TreeDiff::Added] or [TreeDiff::Removed].
+    pub fn diff(&self, other: &Tree<T>) -> Vec<TreeDiff>
+    where
+        T: PartialEq,
+    {
+        let common = self.len().min(other.len());
+        let mut diff: Vec<_> = (0..common)
+            .filter(|pos| {
+                self.data[*pos] != other.data[*pos]
+                    || self.level[*pos] != other.level[*pos]
+                    || self.parent[*pos] != other.parent[*pos]
+            })
+            .map(|pos| TreeDiff::Changed(pos.into()))
+            .collect();
+
+        diff.extend((common..other.len()).map(|pos| TreeDiff::Added(pos.into())));
+        diff.extend((common..self.len()).map(|pos| TreeDiff::Removed(pos.into())));
+        diff
+    }
+
+    /// An [Iterator] in *in-order*, for binary-shaped trees: the first child
+    /// (the "left"), then the node, then the second child (the "right").
+    ///
+    /// Only meaningful for nodes with at most 2 children: any extra children are
+    /// visited after the second one, as if they were also at the "right".
+    pub fn in_order(&self) -> impl Iterator<Item = Node<'_, T>> {
+        let mut order = Vec::with_capacity(self.len());
+        let mut stack = vec![(0, false)];
+        while let Some((idx, visited)) = stack.pop() {
+            if visited {
+                order.push(idx);
+                continue;
+            }
+            let children: Vec<_> = self._direct_children(idx).collect();
+            for child in children.iter().skip(1).rev() {
+                stack.push((*child, false));
+            }
+            stack.push((idx, true));
+            if let Some(left) = children.first() {
+                stack.push((*left, false));
+            }
+        }
+        order
+            .into_iter()
+            .map(move |idx| self._make_node(idx.into()))
+    }
+
+    /// The `(level, data)` of each node in pre-order, the shape of the indented
+    /// lists of UI & terminal libraries.
+    pub fn to_display_rows(&self) -> Vec<(usize, &T)> {
+        self.level.iter().copied().zip(self.data.iter()).collect()
+    }
+
+    /// An [Iterator] of the [TreeEvent] of a depth-first walk, in one pass
+    /// comparing the levels of consecutive nodes.
+    ///
+    /// Each [TreeEvent::Open] is balanced by a [TreeEvent::Close].
+    pub fn emit_events(&self) -> impl Iterator<Item = TreeEvent<&T>> {
+        let mut pos = 0;
+        // The nodes opened & not closed, so the ancestors of `pos`
+        let mut open = 0;
+        std::iter::from_fn(move || {
+            // Past the end, close everything
+            let level = self.level.get(pos).copied().unwrap_or(0);
+            if open > level {
+                open -= 1;
+                return Some(TreeEvent::Close);
+            }
+            let x = self.data.get(pos)?;
+            pos += 1;
+            if self._is_leaf(pos - 1) {
+                Some(TreeEvent::Leaf(x))
             } else {
-                "├──"
+                open += 1;
+                Some(TreeEvent::Open(x))
             }
-            .to_string();
+        })
+    }
+
+    /// Index the nodes by level, so all the nodes at a level are a slice.
+    ///
+    /// It is a counting sort of the levels, in two passes.
+    pub fn level_index(&self) -> LevelIndex {
+        let depth = self.level.iter().copied().max().unwrap_or(0) + 1;
+        let mut starts = vec![0; depth + 1];
+        for level in &self.level {
+            starts[level + 1] += 1;
+        }
+        for level in 1..starts.len() {
+            starts[level] += starts[level - 1];
+        }
+
+        let mut next = starts.clone();
+        let mut ids = vec![NodeId::ROOT; self.len()];
+        for (pos, level) in self.level.iter().enumerate() {
+            ids[next[*level]] = pos.into();
+            next[*level] += 1;
+        }
+        LevelIndex { ids, starts }
+    }
+
+    /// Print each node in its own line, prefixed by `indent` repeated by its level.
+    ///
+    /// It is the inverse of [Tree::from_indented].
+    pub fn to_outline(&self, indent: &str) -> String
+    where
+        T: Display,
+    {
+        let mut out = String::new();
+        for (x, level) in self.data.iter().zip(self.level.iter()) {
+            out.push_str(&indent.repeat(*level));
+            out.push_str(&x.to_string());
+            out.push('\n');
+        }
+        out
+    }
+
+    /// An [IndentCache] of the levels up to `max_level`, or the depth of this
+    /// tree if deeper, to pretty-print many trees with [Self::print_with] &
+    /// [Self::write_tree_with].
+    pub fn indent_cache(&self, max_level: usize) -> IndentCache {
+        IndentCache::new(max_level.max(self.level.iter().copied().max().unwrap_or(0)))
+    }
 
+    /// Split the prefix (columns + branch) of each line of the pretty-printed tree
+    /// in slices of the `cache`, and pass them with the data to `line`
+    fn _print_lines<'c, E>(
+        &self,
+        cache: &'c IndentCache,
+        mut line: impl FnMut(&[&'c str], &T) -> Result<(), E>,
+    ) -> Result<(), E> {
+        // If the node is the last child of its parent, in a backward pass
+        let mut is_last = vec![true; self.len()];
+        let mut seen = vec![false; self.len()];
+        for pos in (1..self.len()).rev() {
+            let parent = self.parent[pos];
+            is_last[pos] = !seen[parent];
+            seen[parent] = true;
+        }
+
+        // For each level above, if the ancestor on it has siblings after it
+        let mut open: Vec<bool> = Vec::with_capacity(cache.max_level());
+        let mut prefix: Vec<&str> = Vec::with_capacity(cache.max_level() + 1);
+        for (pos, x) in self.data.iter().enumerate() {
             let level = self.level[pos];
-            let mut col = String::with_capacity(level * 2);
-            for _i in 1..level {
-                match pos.cmp(&last) {
-                    Ordering::Greater => branch.push_str(&"──".repeat(level)),
-                    Ordering::Less => col.push_str("├   "),
-                    Ordering::Equal => branch.push_str("──"),
+            prefix.clear();
+            if level == 0 {
+                prefix.push(PRINT_ROOT);
+            } else {
+                open.truncate(level - 1);
+                // Each run of the same column is a single slice of the cache
+                let mut start = 0;
+                while start < open.len() {
+                    let run = open[start..]
+                        .iter()
+                        .take_while(|o| **o == open[start])
+                        .count();
+                    cache.push_columns(&mut prefix, open[start], run);
+                    start += run;
                 }
+                prefix.push(if is_last[pos] {
+                    PRINT_LAST
+                } else {
+                    PRINT_BRANCH
+                });
+                open.push(!is_last[pos]);
             }
-            writeln!(f, "{}{} {}", col, branch, x)?;
+            line(&prefix, x)?;
         }
         Ok(())
     }
+
+    /// Pretty-print the tree
+    pub fn print(&self, f: &mut Formatter<'_>) -> std::fmt::Result
+    where
+        T: Display,
+    {
+        self.print_with(f, &self.indent_cache(0))
+    }
+
+    /// Like [Self::print], reusing the columns of the `cache`.
+    pub fn print_with(&self, f: &mut Formatter<'_>, cache: &IndentCache) -> std::fmt::Result
+    where
+        T: Display,
+    {
+        self._print_lines(cache, |prefix, x| {
+            for p in prefix {
+                f.write_str(p)?;
+            }
+            writeln!(f, "{}", x)
+        })
+    }
+
+    /// Pretty-print the tree into a [std::io::Write], like a file or a buffer
+    pub fn write_tree<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()>
+    where
+        T: Display,
+    {
+        self.write_tree_with(w, &self.indent_cache(0))
+    }
+
+    /// Like [Self::write_tree], reusing the columns of the `cache`.
+    pub fn write_tree_with<W: std::io::Write>(
+        &self,
+        w: &mut W,
+        cache: &IndentCache,
+    ) -> std::io::Result<()>
+    where
+        T: Display,
+    {
+        self._print_lines(cache, |prefix, x| {
+            for p in prefix {
+                w.write_all(p.as_bytes())?;
+            }
+            writeln!(w, "{}", x)
+        })
+    }
+}
+
+impl<T: Debug> Tree<Option<T>> {
+    /// Remove the nodes with [None], with all their subtree, and unwrap the rest.
+    ///
+    /// Returns [None] if the root is [None].
+    pub fn flatten_options(mut self) -> Option<Tree<T>> {
+        self.data[0].as_ref()?;
+        let mut keep = vec![true; self.len()];
+        let mut pos = 0;
+        while pos < self.len() {
+            if self.data[pos].is_none() {
+                let end = self._subtree_end(pos);
+                keep[pos..end].iter_mut().for_each(|k| *k = false);
+                pos = end;
+            } else {
+                pos += 1;
+            }
+        }
+        self._retain_mask(&keep);
+
+        Some(Tree {
+            data: self.data.into_iter().flatten().collect(),
+            level: self.level,
+            parent: self.parent,
+        })
+    }
+}
+
+impl<T: Debug> Tree<Box<T>> {
+    /// Consume tree and move the data out of their [Box], the inverse of [Tree::boxed].
+    pub fn unboxed(self) -> Tree<T> {
+        self.map_into(|x| *x)
+    }
+}
+
+impl Tree<String> {
+    /// Consume tree and share the equal strings in a single [Arc], like the
+    /// names repeated in a filesystem, keeping the shape.
+    pub fn intern(self) -> Tree<Arc<str>> {
+        let mut seen: HashSet<Arc<str>> = HashSet::new();
+        self.map_into(|x| match seen.get(x.as_str()) {
+            Some(shared) => shared.clone(),
+            None => {
+                let shared: Arc<str> = Arc::from(x);
+                seen.insert(shared.clone());
+                shared
+            }
+        })
+    }
+
+    /// Like [Self::intern], but sharing the strings in a single [Rc], for trees
+    /// that stay in one thread.
+    pub fn intern_rc(self) -> Tree<Rc<str>> {
+        let mut seen: HashSet<Rc<str>> = HashSet::new();
+        self.map_into(|x| match seen.get(x.as_str()) {
+            Some(shared) => shared.clone(),
+            None => {
+                let shared: Rc<str> = Rc::from(x);
+                seen.insert(shared.clone());
+                shared
+            }
+        })
+    }
+
+    /// Parse a [Tree] from `text` with a node per line, where the level is
+    /// how many times the line starts with `indent`.
+    ///
+    /// The empty lines are skipped. It is the inverse of [Tree::to_outline].
+    ///
+    /// # Errors
+    ///
+    /// Returns [TreeError::Empty] if there are no lines, or [TreeError::InvalidLevel]
+    /// if the first line is indented or a line is indented more than one level
+    /// below the previous one.
+    pub fn from_indented(text: &str, indent: &str) -> Result<Tree<String>, TreeError> {
+        let mut tree: Option<Tree<String>> = None;
+        // The last node seen at each level
+        let mut last: Vec<NodeId> = Vec::new();
+        for line in text.lines().filter(|x| !x.trim().is_empty()) {
+            let mut rest = line;
+            let mut level = 0;
+            while !indent.is_empty() && rest.starts_with(indent) {
+                rest = &rest[indent.len()..];
+                level += 1;
+            }
+
+            match tree.as_mut() {
+                None => {
+                    if level > 0 {
+                        return Err(TreeError::InvalidLevel(0.into()));
+                    }
+                    tree = Some(Tree::new(rest.to_string()));
+                    last.push(NodeId::ROOT);
+                }
+                Some(tree) => {
+                    if level == 0 || level > last.len() {
+                        return Err(TreeError::InvalidLevel(tree.len().into()));
+                    }
+                    let id = tree.push_with_level(rest.to_string(), level, last[level - 1]);
+                    last.truncate(level);
+                    last.push(id);
+                }
+            }
+        }
+        tree.ok_or(TreeError::Empty)
+    }
 }
 
 impl<T: Debug + Display> Display for Tree<T> {
@@ -401,3 +2135,23 @@ impl<T: Debug + Display> Display for Tree<T> {
         self.print(f)
     }
 }
+
+/// Build a [Tree] from the `(data, level, parent)` vectors, checking they are
+/// consistent with [Tree::validate].
+impl<T: Debug> TryFrom<(Vec<T>, Vec<usize>, Vec<usize>)> for Tree<T> {
+    type Error = TreeError;
+
+    fn try_from(
+        (data, level, parent): (Vec<T>, Vec<usize>, Vec<usize>),
+    ) -> Result<Self, Self::Error> {
+        let tree = Tree {
+            data,
+            level,
+            parent,
+        };
+        match tree.validate() {
+            Ok(()) => Ok(tree),
+            Err(errors) => Err(errors[0].into()),
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use std::fmt::{Debug, Display, Formatter};
+
+use crate::prelude::*;
+use crate::tree::{subtree_end, validate_columns};
+
+/// A read-only, borrowed, *flattened in pre-order*, Tree.
+///
+/// It works over the `(data, level, parent)` slices of a [Tree], or of any
+/// externally-owned columnar data (like Arrow arrays).
+pub struct TreeView<'a, T> {
+    pub(crate) data: &'a [T],
+    pub(crate) level: &'a [usize],
+    pub(crate) parent: &'a [usize],
+}
+
+impl<T> Clone for TreeView<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for TreeView<'_, T> {}
+
+impl<T: Debug> Debug for TreeView<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TreeView")
+            .field("data", &self.data)
+            .field("level", &self.level)
+            .field("parent", &self.parent)
+            .finish()
+    }
+}
+
+impl<'a, T: Debug> TreeView<'a, T> {
+    /// Create a new [TreeView] over the slices, checking they are consistent
+    /// like [Tree::validate].
+    ///
+    /// # Errors
+    ///
+    /// Returns the first problem found, as a [TreeError].
+    pub fn new(data: &'a [T], level: &'a [usize], parent: &'a [usize]) -> Result<Self, TreeError> {
+        if let Some(err) = validate_columns(data.len(), level, parent).first() {
+            return Err((*err).into());
+        }
+        Ok(TreeView {
+            data,
+            level,
+            parent,
+        })
+    }
+
+    pub(crate) fn _make_node(&self, idx: usize) -> ViewNode<'a, T> {
+        ViewNode {
+            id: idx.into(),
+            data: &self.data[idx],
+            view: *self,
+        }
+    }
+
+    /// Returns the number of elements in the view.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns `true` if the view contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Get the [`ViewNode<T>`] from his [NodeId]
+    pub fn node(&self, id: NodeId) -> Option<ViewNode<'a, T>> {
+        if id.to_index() < self.len() {
+            Some(self._make_node(id.to_index()))
+        } else {
+            None
+        }
+    }
+
+    /// Get the root [`ViewNode<T>`]
+    pub fn root(&self) -> ViewNode<'a, T> {
+        self._make_node(0)
+    }
+
+    /// An [Iterator] of all the nodes in pre-order.
+    pub fn iter(&self) -> impl Iterator<Item = ViewNode<'a, T>> {
+        let view = *self;
+        (0..self.len()).map(move |idx| view._make_node(idx))
+    }
+
+    /// A slice view of the data
+    pub fn as_data(&self) -> &'a [T] {
+        self.data
+    }
+
+    /// A slice view of the level
+    pub fn as_level(&self) -> &'a [usize] {
+        self.level
+    }
+
+    /// A slice view of the parents
+    pub fn as_parents(&self) -> &'a [usize] {
+        self.parent
+    }
+
+    /// Get the level from a [NodeId]
+    pub fn get_level(&self, of: NodeId) -> usize {
+        self.level[of.to_index()]
+    }
+}
+
+impl<T: Debug> Tree<T> {
+    /// A read-only [TreeView] of this tree.
+    pub fn as_view(&self) -> TreeView<'_, T> {
+        TreeView {
+            data: &self.data,
+            level: &self.level,
+            parent: &self.parent,
+        }
+    }
+}
+
+/// An immutable view of the [Self::data] in the [TreeView] with their [NodeId].
+pub struct ViewNode<'a, T> {
+    /// Node ID.
+    pub id: NodeId,
+    /// Data.
+    pub data: &'a T,
+    /// View containing the node.
+    pub(crate) view: TreeView<'a, T>,
+}
+
+impl<T> Clone for ViewNode<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ViewNode<'_, T> {}
+
+impl<'a, T: Debug> ViewNode<'a, T> {
+    pub fn level(&self) -> usize {
+        self.view.level[self.id.to_index()]
+    }
+    pub fn parent(&self) -> usize {
+        self.view.parent[self.id.to_index()]
+    }
+
+    /// The [NodeId] of the parent of this [ViewNode], or [None] for the root,
+    /// like [Node::parent_id].
+    pub fn parent_id(&self) -> Option<NodeId> {
+        if self.id.is_root() {
+            None
+        } else {
+            Some(self.parent().into())
+        }
+    }
+
+    /// An [Iterator] of the parents from this [ViewNode], like [Node::parents].
+    pub fn parents(&self) -> impl Iterator<Item = ViewNode<'a, T>> {
+        let view = self.view;
+        let mut idx = self.id.to_index();
+        std::iter::from_fn(move || {
+            if idx > 0 {
+                idx = view.parent[idx];
+                Some(view._make_node(idx))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// An [Iterator] of the children from this [ViewNode], like [Node::children].
+    pub fn children(&self) -> impl Iterator<Item = ViewNode<'a, T>> {
+        let view = self.view;
+        let idx = self.id.to_index();
+        (idx + 1..subtree_end(view.level, idx)).map(move |idx| view._make_node(idx))
+    }
+
+    /// An [Iterator] of the siblings from this [ViewNode], like [Node::siblings].
+    pub fn siblings(&self) -> impl Iterator<Item = ViewNode<'a, T>> {
+        let view = self.view;
+        let idx = self.id.to_index();
+        let level = self.level();
+        (0..view.len())
+            .filter(move |pos| view.level[*pos] == level && *pos != idx)
+            .map(move |pos| view._make_node(pos))
+    }
+}
+
+impl<T: Debug> Debug for ViewNode<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write! {f, "{:?}:{:?}", self.id, self.data}
+    }
+}
+
+impl<T: Display> Display for ViewNode<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write! {f, "{}", self.data}
+    }
+}